@@ -73,6 +73,15 @@ impl DnfExpr {
         Some(res)
     }
 
+    /// Returns `true` if no set of options can make this expression evaluate to `true`.
+    ///
+    /// Besides atoms that are required to be both enabled and disabled, this knows about atoms
+    /// describing the compilation target that can't be enabled at the same time, like `unix` and
+    /// `windows`. Expressions containing errors are never considered unsatisfiable.
+    pub fn is_unsatisfiable(&self) -> bool {
+        self.conjunctions.iter().all(|conj| conj.is_unsatisfiable())
+    }
+
     /// Returns `CfgDiff` objects that would enable this directive if applied to `opts`.
     pub fn compute_enable_hints<'a>(
         &'a self,
@@ -162,6 +171,18 @@ impl Conjunction {
     }
 }
 
+impl Conjunction {
+    fn is_unsatisfiable(&self) -> bool {
+        if self.literals.iter().any(|lit| lit.var.is_none()) {
+            return false;
+        }
+        self.literals
+            .iter()
+            .enumerate()
+            .any(|(i, lit)| self.literals[i + 1..].iter().any(|other| lit.contradicts(other)))
+    }
+}
+
 impl fmt::Display for Conjunction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.literals.len() != 1 {
@@ -197,6 +218,57 @@ impl Literal {
     }
 }
 
+impl Literal {
+    fn contradicts(&self, other: &Literal) -> bool {
+        let (atom, other_atom) = match (&self.var, &other.var) {
+            (Some(atom), Some(other_atom)) => (atom, other_atom),
+            _ => return false,
+        };
+        match (self.negate, other.negate) {
+            (false, true) | (true, false) => atom == other_atom,
+            (false, false) => excludes(atom, other_atom),
+            (true, true) => false,
+        }
+    }
+}
+
+/// Returns `true` if `a` and `b` can never be enabled at the same time, because they describe
+/// different values of the same single-valued property of the compilation target.
+fn excludes(a: &CfgAtom, b: &CfgAtom) -> bool {
+    fn family(atom: &CfgAtom) -> Option<&str> {
+        match atom {
+            CfgAtom::Flag(flag) if flag == "unix" || flag == "windows" => Some(flag),
+            CfgAtom::KeyValue { key, value } if key == "target_family" => Some(value),
+            _ => None,
+        }
+    }
+
+    // Some targets belong to several families (eg. `unix` and `wasm`), but none is both `unix`
+    // and `windows`.
+    if let (Some(a), Some(b)) = (family(a), family(b)) {
+        return matches!((a, b), ("unix", "windows") | ("windows", "unix"));
+    }
+    match (a, b) {
+        (
+            CfgAtom::KeyValue { key: a_key, value: a_value },
+            CfgAtom::KeyValue { key: b_key, value: b_value },
+        ) => {
+            a_key == b_key
+                && a_value != b_value
+                && matches!(
+                    &**a_key,
+                    "target_arch"
+                        | "target_os"
+                        | "target_env"
+                        | "target_endian"
+                        | "target_pointer_width"
+                        | "target_vendor"
+                )
+        }
+        _ => false,
+    }
+}
+
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.negate {
@@ -37,6 +37,17 @@ fn check_why_inactive(input: &str, opts: &CfgOptions, expect: Expect) {
     expect.assert_eq(&why_inactive);
 }
 
+fn check_unsatisfiable(input: &str, expected: bool) {
+    let (tt, _) = {
+        let source_file = ast::SourceFile::parse(input).ok().unwrap();
+        let tt = source_file.syntax().descendants().find_map(ast::TokenTree::cast).unwrap();
+        ast_to_token_tree(&tt)
+    };
+    let cfg = CfgExpr::parse(&tt);
+    let dnf = DnfExpr::new(cfg);
+    assert_eq!(dnf.is_unsatisfiable(), expected, "{}", input);
+}
+
 #[track_caller]
 fn check_enable_hints(input: &str, opts: &CfgOptions, expected_hints: &[&str]) {
     let (tt, _) = {
@@ -160,6 +171,20 @@ fn hints_impossible() {
     check_enable_hints("#![cfg(all(test, not(test)))]", &opts, &[]);
 }
 
+#[test]
+fn unsatisfiable() {
+    check_unsatisfiable("#![cfg(all(test, not(test)))]", true);
+    check_unsatisfiable("#![cfg(all(unix, windows))]", true);
+    check_unsatisfiable("#![cfg(all(unix, target_family = \"windows\"))]", true);
+    check_unsatisfiable("#![cfg(all(target_os = \"linux\", target_os = \"macos\"))]", true);
+    check_unsatisfiable("#![cfg(any(all(unix, windows), all(a, not(a))))]", true);
+
+    check_unsatisfiable("#![cfg(all(unix, target_family = \"wasm\"))]", false);
+    check_unsatisfiable("#![cfg(all(target_feature = \"sse\", target_feature = \"sse2\"))]", false);
+    check_unsatisfiable("#![cfg(any(all(unix, windows), test))]", false);
+    check_unsatisfiable("#![cfg(all(not(unix), not(windows)))]", false);
+}
+
 #[test]
 fn why_inactive() {
     let mut opts = CfgOptions::default();
@@ -3,7 +3,8 @@
 pub use hir_def::db::*;
 pub use hir_expand::db::{
     AstDatabase, AstDatabaseStorage, AstIdMapQuery, HygieneFrameQuery, InternEagerExpansionQuery,
-    InternMacroQuery, MacroArgTextQuery, MacroDefQuery, MacroExpandQuery, ParseMacroExpansionQuery,
+    InternMacroQuery, MacroArgTextQuery, MacroDefQuery, MacroExpandQuery, MacroTokenLimitQuery,
+    ParseMacroExpansionQuery, DEFAULT_TOKEN_LIMIT,
 };
 pub use hir_ty::db::*;
 
@@ -1,6 +1,15 @@
 //! FIXME: write short doc here
 pub use hir_def::diagnostics::{
-    InactiveCode, UnresolvedMacroCall, UnresolvedModule, UnresolvedProcMacro,
+    BadSelfImport, ContradictoryCfg, CrateSelfAlias, DeprecatedWithoutNote,
+    DisallowedNestedImplTrait, DuplicateExportedSymbol, DuplicateLangItem,
+    DuplicateMacroDefinition, EmptyEnum, GlobOfSelf, GlobOverridesPrelude, HelperAttrWithoutDerive,
+    InactiveCode, IneffectiveMacroExport, InvalidAttributePlacement, InvalidRepr,
+    MacroExpansionTooLarge, MacroUsedBeforeDefinition, ModuleInlineAndFile, NonAsciiIdentifier,
+    NonPortablePath, OverflowingLiteral, OverlappingImpl, PreludeShadow,
+    ReExportOfPrivateModuleItem, RedundantExternCrateCore, RedundantVisibilityOnTraitItem,
+    SelfReferentialImport, SuperBeyondRoot, UnknownToolLint, UnresolvedDerivePath,
+    UnresolvedMacroCall, UnresolvedModule, UnresolvedProcMacro, UnresolvedTraitImport,
+    UnsupportedMacroPosition, UnusedImport, WrongNumberOfGenericArgs,
 };
 pub use hir_expand::diagnostics::{
     Diagnostic, DiagnosticCode, DiagnosticSink, DiagnosticSinkBuilder,
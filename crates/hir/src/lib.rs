@@ -41,6 +41,7 @@ use hir_def::{
     expr::{BindingAnnotation, LabelId, Pat, PatId},
     item_tree::ItemTreeNode,
     lang_item::LangItemTarget,
+    nameres::{group_by_module, summarize},
     per_ns::PerNs,
     resolver::{HasResolver, Resolver},
     src::HasSource as _,
@@ -63,7 +64,7 @@ use hir_ty::{
     WhereClause,
 };
 use itertools::Itertools;
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 use stdx::{format_to, impl_from};
 use syntax::{
     ast::{self, AttrsOwner, NameOwner},
@@ -73,6 +74,8 @@ use tt::{Ident, Leaf, Literal, TokenTree};
 
 use crate::db::{DefDatabase, HirDatabase};
 
+#[cfg(feature = "metrics")]
+pub use crate::semantics::ChildBySourceStats;
 pub use crate::{
     attrs::{HasAttrs, Namespace},
     has_source::HasSource,
@@ -176,6 +179,27 @@ impl Crate {
         db.crate_graph()[self.id].display_name.clone()
     }
 
+    /// Counts the diagnostics that name resolution reports in this crate by their code, like
+    /// `unresolved-import`.
+    pub fn diagnostic_summary(self, db: &dyn HirDatabase) -> FxHashMap<&'static str, usize> {
+        summarize(self.id, db.upcast())
+    }
+
+    /// Returns the modules that name resolution reports diagnostics in, together with the number
+    /// of diagnostics in each.
+    pub fn modules_with_diagnostics(self, db: &dyn HirDatabase) -> Vec<(Module, usize)> {
+        let def_map = db.crate_def_map(self.id);
+        let mut modules: Vec<_> = group_by_module(def_map.diagnostics().to_vec())
+            .into_iter()
+            .map(|(local_id, diags)| (local_id, diags.len()))
+            .collect();
+        modules.sort_by_key(|&(local_id, _)| u32::from(local_id.into_raw()));
+        modules
+            .into_iter()
+            .map(|(local_id, count)| (Module { id: def_map.module_id(local_id) }, count))
+            .collect()
+    }
+
     pub fn query_external_importables(
         self,
         db: &dyn DefDatabase,
@@ -338,6 +362,16 @@ impl ModuleDef {
             None => return,
         };
 
+        match self {
+            ModuleDef::Const(it) => {
+                hir_def::diagnostics::validate_body(db.upcast(), it.id.into(), sink)
+            }
+            ModuleDef::Static(it) => {
+                hir_def::diagnostics::validate_body(db.upcast(), it.id.into(), sink)
+            }
+            _ => {}
+        }
+        hir_def::diagnostics::validate_module_item(db.upcast(), id, sink);
         hir_ty::diagnostics::validate_module_item(db, module.id.krate(), id, sink)
     }
 }
@@ -371,12 +405,8 @@ impl Module {
 
     /// Iterates over all child modules.
     pub fn children(self, db: &dyn HirDatabase) -> impl Iterator<Item = Module> {
-        let def_map = self.id.def_map(db.upcast());
-        let children = def_map[self.id.local_id]
-            .children
-            .iter()
-            .map(|(_, module_id)| Module { id: def_map.module_id(*module_id) })
-            .collect::<Vec<_>>();
+        let children =
+            db.module_children(self.id).iter().map(|&id| Module { id }).collect::<Vec<_>>();
         children.into_iter()
     }
 
@@ -436,6 +466,8 @@ impl Module {
         });
         let def_map = self.id.def_map(db.upcast());
         def_map.add_diagnostics(db.upcast(), self.id.local_id, sink);
+        let used_traits = self.used_traits(db);
+        hir_def::diagnostics::validate_imports(db.upcast(), self.id, &used_traits, sink);
         for decl in self.declarations(db) {
             match decl {
                 crate::ModuleDef::Function(f) => f.diagnostics(db, sink),
@@ -452,6 +484,7 @@ impl Module {
         }
 
         for impl_def in self.impl_defs(db) {
+            hir_def::diagnostics::validate_impl(db.upcast(), impl_def.id, sink);
             for item in impl_def.items(db) {
                 if let AssocItem::Function(f) = item {
                     f.diagnostics(db, sink);
@@ -460,6 +493,59 @@ impl Module {
         }
     }
 
+    /// Returns the traits whose methods or associated items are used in the bodies of this module
+    /// and its descendants. Using them that way doesn't require naming the trait, only importing
+    /// it.
+    fn used_traits(self, db: &dyn HirDatabase) -> FxHashSet<TraitId> {
+        let mut bodies: Vec<DefWithBodyId> = Vec::new();
+        let mut assoc_items = Vec::new();
+        let mut modules = vec![self];
+        while let Some(module) = modules.pop() {
+            modules.extend(module.children(db));
+            for decl in module.declarations(db) {
+                match decl {
+                    ModuleDef::Function(it) => bodies.push(it.id.into()),
+                    ModuleDef::Const(it) => bodies.push(it.id.into()),
+                    ModuleDef::Static(it) => bodies.push(it.id.into()),
+                    ModuleDef::Trait(it) => assoc_items.extend(it.items(db)),
+                    _ => {}
+                }
+            }
+            for impl_def in module.impl_defs(db) {
+                assoc_items.extend(impl_def.items(db));
+            }
+        }
+        bodies.extend(assoc_items.into_iter().filter_map(|item| match item {
+            AssocItem::Function(it) => Some(DefWithBodyId::from(it.id)),
+            AssocItem::Const(it) => Some(it.id.into()),
+            AssocItem::TypeAlias(_) => None,
+        }));
+
+        let trait_of = |item: AssocItemId| {
+            let container = match item {
+                AssocItemId::FunctionId(it) => it.lookup(db.upcast()).container,
+                AssocItemId::ConstId(it) => it.lookup(db.upcast()).container,
+                AssocItemId::TypeAliasId(it) => it.lookup(db.upcast()).container,
+            };
+            match container {
+                AssocContainerId::TraitId(it) => Some(it),
+                _ => None,
+            }
+        };
+        let mut traits = FxHashSet::default();
+        for def in bodies {
+            let body = db.body(def);
+            let infer = db.infer(def);
+            let exprs = body.exprs.iter().flat_map(|(id, _)| {
+                let method = infer.method_resolution(id).map(AssocItemId::FunctionId);
+                method.into_iter().chain(infer.assoc_resolutions_for_expr(id))
+            });
+            let pats = body.pats.iter().filter_map(|(id, _)| infer.assoc_resolutions_for_pat(id));
+            traits.extend(exprs.chain(pats).filter_map(trait_of));
+        }
+        traits
+    }
+
     pub fn declarations(self, db: &dyn HirDatabase) -> Vec<ModuleDef> {
         let def_map = self.id.def_map(db.upcast());
         def_map[self.id.local_id].scope.declarations().map(ModuleDef::from).collect()
@@ -871,6 +957,7 @@ impl Function {
     pub fn diagnostics(self, db: &dyn HirDatabase, sink: &mut DiagnosticSink) {
         let krate = self.module(db).id.krate();
         hir_def::diagnostics::validate_body(db.upcast(), self.id.into(), sink);
+        hir_def::diagnostics::validate_module_item(db.upcast(), self.id.into(), sink);
         hir_ty::diagnostics::validate_module_item(db, krate, self.id.into(), sink);
         hir_ty::diagnostics::validate_body(db, self.id.into(), sink);
     }
@@ -20,6 +20,8 @@ use syntax::{
     match_ast, AstNode, SyntaxNode, SyntaxNodePtr, SyntaxToken, TextSize,
 };
 
+#[cfg(feature = "metrics")]
+pub use crate::semantics::source_to_def::ChildBySourceStats;
 use crate::{
     db::HirDatabase,
     semantics::source_to_def::{ChildContainer, SourceToDefCache, SourceToDefCtx},
@@ -267,6 +269,19 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         T::to_def(&self.imp, src)
     }
 
+    /// Returns how often `child_by_source` was computed and reused for the containers in each
+    /// module while mapping syntax with these `Semantics`.
+    #[cfg(feature = "metrics")]
+    pub fn child_by_source_stats(&self) -> FxHashMap<Module, ChildBySourceStats> {
+        self.imp
+            .s2d_cache
+            .borrow()
+            .stats
+            .iter()
+            .map(|(&module, &stats)| (module.into(), stats))
+            .collect()
+    }
+
     pub fn to_module_def(&self, file: FileId) -> Option<Module> {
         self.imp.to_module_def(file).next()
     }
@@ -771,6 +786,8 @@ to_def_impls![
     (crate::TypeParam, ast::TypeParam, type_param_to_def),
     (crate::LifetimeParam, ast::LifetimeParam, lifetime_param_to_def),
     (crate::ConstParam, ast::ConstParam, const_param_to_def),
+    (crate::GenericDef, ast::WherePred, where_pred_to_def),
+    (crate::Trait, ast::DynTraitType, dyn_trait_to_def),
     (crate::MacroDef, ast::Macro, macro_to_def),
     (crate::Local, ast::IdentPat, bind_pat_to_def),
     (crate::Local, ast::SelfParam, self_param_to_def),
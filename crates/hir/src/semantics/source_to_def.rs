@@ -10,6 +10,8 @@ use hir_def::{
     ImplId, LifetimeParamId, ModuleId, StaticId, StructId, TraitId, TypeAliasId, TypeParamId,
     UnionId, VariantId,
 };
+#[cfg(feature = "metrics")]
+use hir_def::{HasModule, Lookup};
 use hir_expand::{name::AsName, AstId, MacroDefKind};
 use rustc_hash::FxHashMap;
 use smallvec::SmallVec;
@@ -21,7 +23,22 @@ use syntax::{
 
 use crate::{db::HirDatabase, InFile, MacroDefId};
 
-pub(super) type SourceToDefCache = FxHashMap<ChildContainer, DynMap>;
+#[derive(Default)]
+pub(super) struct SourceToDefCache {
+    maps: FxHashMap<ChildContainer, DynMap>,
+    /// How often the maps of the containers in each module were computed or reused.
+    #[cfg(feature = "metrics")]
+    pub(super) stats: FxHashMap<ModuleId, ChildBySourceStats>,
+}
+
+/// How often `child_by_source` was computed for the containers in a module, and how often a
+/// cached result was used instead.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ChildBySourceStats {
+    pub computed: usize,
+    pub cache_hits: usize,
+}
 
 pub(super) struct SourceToDefCtx<'a, 'b> {
     pub(super) db: &'b dyn HirDatabase,
@@ -145,24 +162,34 @@ impl SourceToDefCtx<'_, '_> {
         Some((container, label_id))
     }
 
+    fn dyn_map(&mut self, container: ChildContainer) -> &DynMap {
+        let db = self.db;
+        #[cfg(feature = "metrics")]
+        {
+            let stats = self.cache.stats.entry(container.module(db)).or_default();
+            if self.cache.maps.contains_key(&container) {
+                stats.cache_hits += 1;
+            } else {
+                stats.computed += 1;
+            }
+        }
+        self.cache.maps.entry(container).or_insert_with(|| container.child_by_source(db))
+    }
+
     fn to_def<Ast: AstNode + 'static, ID: Copy + 'static>(
         &mut self,
         src: InFile<Ast>,
         key: Key<Ast, ID>,
     ) -> Option<ID> {
         let container = self.find_container(src.as_ref().map(|it| it.syntax()))?;
-        let db = self.db;
-        let dyn_map =
-            &*self.cache.entry(container).or_insert_with(|| container.child_by_source(db));
+        let dyn_map = self.dyn_map(container);
         dyn_map[key].get(&src).copied()
     }
 
     pub(super) fn type_param_to_def(&mut self, src: InFile<ast::TypeParam>) -> Option<TypeParamId> {
         let container: ChildContainer =
             self.find_generic_param_container(src.as_ref().map(|it| it.syntax()))?.into();
-        let db = self.db;
-        let dyn_map =
-            &*self.cache.entry(container).or_insert_with(|| container.child_by_source(db));
+        let dyn_map = self.dyn_map(container);
         dyn_map[keys::TYPE_PARAM].get(&src).copied()
     }
 
@@ -172,9 +199,7 @@ impl SourceToDefCtx<'_, '_> {
     ) -> Option<LifetimeParamId> {
         let container: ChildContainer =
             self.find_generic_param_container(src.as_ref().map(|it| it.syntax()))?.into();
-        let db = self.db;
-        let dyn_map =
-            &*self.cache.entry(container).or_insert_with(|| container.child_by_source(db));
+        let dyn_map = self.dyn_map(container);
         dyn_map[keys::LIFETIME_PARAM].get(&src).copied()
     }
 
@@ -184,12 +209,27 @@ impl SourceToDefCtx<'_, '_> {
     ) -> Option<ConstParamId> {
         let container: ChildContainer =
             self.find_generic_param_container(src.as_ref().map(|it| it.syntax()))?.into();
-        let db = self.db;
-        let dyn_map =
-            &*self.cache.entry(container).or_insert_with(|| container.child_by_source(db));
+        let dyn_map = self.dyn_map(container);
         dyn_map[keys::CONST_PARAM].get(&src).copied()
     }
 
+    pub(super) fn where_pred_to_def(
+        &mut self,
+        src: InFile<ast::WherePred>,
+    ) -> Option<GenericDefId> {
+        let container: ChildContainer =
+            self.find_generic_param_container(src.as_ref().map(|it| it.syntax()))?.into();
+        let dyn_map = self.dyn_map(container);
+        dyn_map[keys::WHERE_PREDICATE].get(&src).copied()
+    }
+
+    pub(super) fn dyn_trait_to_def(&mut self, src: InFile<ast::DynTraitType>) -> Option<TraitId> {
+        let container: ChildContainer =
+            self.find_generic_param_container(src.as_ref().map(|it| it.syntax()))?.into();
+        let dyn_map = self.dyn_map(container);
+        dyn_map[keys::DYN_TRAIT].get(&src).copied()
+    }
+
     // FIXME: use DynMap as well?
     pub(super) fn macro_to_def(&mut self, src: InFile<ast::Macro>) -> Option<MacroDefId> {
         let file_ast_id = self.db.ast_id_map(src.file_id).ast_id(&src.value);
@@ -331,4 +371,19 @@ impl ChildContainer {
             ChildContainer::GenericDefId(it) => it.child_by_source(db),
         }
     }
+
+    #[cfg(feature = "metrics")]
+    fn module(self, db: &dyn HirDatabase) -> ModuleId {
+        let db = db.upcast();
+        match self {
+            ChildContainer::DefWithBodyId(it) => it.module(db),
+            ChildContainer::ModuleId(it) => it,
+            ChildContainer::TraitId(it) => it.lookup(db).container,
+            ChildContainer::ImplId(it) => it.lookup(db).container,
+            ChildContainer::EnumId(it) => it.lookup(db).container,
+            ChildContainer::VariantId(it) => it.module(db),
+            ChildContainer::TypeAliasId(it) => it.lookup(db).module(db),
+            ChildContainer::GenericDefId(it) => it.module(db),
+        }
+    }
 }
@@ -233,16 +233,23 @@ impl SourceAnalyzer {
     ) -> Option<PathResolution> {
         let parent = || path.syntax().parent();
         let mut prefer_value_ns = false;
+        let mut items_only = false;
         if let Some(path_expr) = parent().and_then(ast::PathExpr::cast) {
-            let expr_id = self.expr_id(db, &path_expr.into())?;
-            let infer = self.infer.as_ref()?;
-            if let Some(assoc) = infer.assoc_resolutions_for_expr(expr_id) {
-                return Some(PathResolution::AssocItem(assoc.into()));
-            }
-            if let Some(VariantId::EnumVariantId(variant)) =
-                infer.variant_resolution_for_expr(expr_id)
-            {
-                return Some(PathResolution::Def(ModuleDef::Variant(variant.into())));
+            match self.expr_id(db, &path_expr.into()) {
+                Some(expr_id) => {
+                    let infer = self.infer.as_ref()?;
+                    if let Some(assoc) = infer.assoc_resolutions_for_expr(expr_id) {
+                        return Some(PathResolution::AssocItem(assoc.into()));
+                    }
+                    if let Some(VariantId::EnumVariantId(variant)) =
+                        infer.variant_resolution_for_expr(expr_id)
+                    {
+                        return Some(PathResolution::Def(ModuleDef::Variant(variant.into())));
+                    }
+                }
+                // Expressions that aren't lowered to a body, like enum discriminants and array
+                // lengths in types, can't refer to local bindings.
+                None => items_only = true,
             }
             prefer_value_ns = true;
         }
@@ -296,6 +303,10 @@ impl SourceAnalyzer {
             }
         }
 
+        if items_only {
+            let resolver = self.resolver.without_expr_scopes();
+            return resolve_hir_path_(db, &resolver, &hir_path, prefer_value_ns);
+        }
         resolve_hir_path_(db, &self.resolver, &hir_path, prefer_value_ns)
     }
 
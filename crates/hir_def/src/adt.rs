@@ -243,10 +243,13 @@ impl HasChildSource<LocalFieldId> for VariantId {
     fn child_source(&self, db: &dyn DefDatabase) -> InFile<ArenaMap<LocalFieldId, Self::Value>> {
         let (src, module_id) = match self {
             VariantId::EnumVariantId(it) => {
-                // I don't really like the fact that we call into parent source
-                // here, this might add to more queries then necessary.
-                let src = it.parent.child_source(db);
-                (src.map(|map| map[it.local_id].kind()), it.parent.lookup(db).container)
+                // Go through the memoized variant map instead of lowering all of the parent's
+                // variants again for every single one of them.
+                let loc = it.parent.lookup(db);
+                let file_id = loc.id.file_id();
+                let root = db.parse_or_expand(file_id).unwrap();
+                let variant = db.variants_attrs_source_map(it.parent)[it.local_id].to_node(&root);
+                (InFile::new(file_id, variant.kind()), loc.container)
             }
             VariantId::StructId(it) => {
                 (it.lookup(db).source(db).map(|it| it.kind()), it.lookup(db).container)
@@ -385,6 +385,11 @@ impl BodySourceMap {
         self.expansions.get(&src).cloned()
     }
 
+    /// Returns the files of all macro calls expanded in the body.
+    pub(crate) fn expansion_files(&self) -> impl Iterator<Item = HirFileId> + '_ {
+        self.expansions.values().copied()
+    }
+
     pub fn pat_syntax(&self, pat: PatId) -> Result<PatSource, SyntheticSyntax> {
         self.pat_map_back[pat].clone()
     }
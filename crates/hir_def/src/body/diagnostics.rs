@@ -2,11 +2,14 @@
 
 use hir_expand::diagnostics::DiagnosticSink;
 
-use crate::diagnostics::{InactiveCode, MacroError, UnresolvedMacroCall, UnresolvedProcMacro};
+use crate::diagnostics::{
+    InactiveCode, InvalidAttributePlacement, MacroError, UnresolvedMacroCall, UnresolvedProcMacro,
+};
 
 #[derive(Debug, Eq, PartialEq)]
 pub(crate) enum BodyDiagnostic {
     InactiveCode(InactiveCode),
+    InvalidAttributePlacement(InvalidAttributePlacement),
     MacroError(MacroError),
     UnresolvedProcMacro(UnresolvedProcMacro),
     UnresolvedMacroCall(UnresolvedMacroCall),
@@ -18,6 +21,9 @@ impl BodyDiagnostic {
             BodyDiagnostic::InactiveCode(diag) => {
                 sink.push(diag.clone());
             }
+            BodyDiagnostic::InvalidAttributePlacement(diag) => {
+                sink.push(diag.clone());
+            }
             BodyDiagnostic::MacroError(diag) => {
                 sink.push(diag.clone());
             }
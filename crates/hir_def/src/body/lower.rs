@@ -14,8 +14,8 @@ use la_arena::Arena;
 use profile::Count;
 use syntax::{
     ast::{
-        self, ArgListOwner, ArrayExprKind, AstChildren, LiteralKind, LoopBodyOwner, NameOwner,
-        SlicePatComponents,
+        self, ArgListOwner, ArrayExprKind, AstChildren, AttrsOwner, LiteralKind, LoopBodyOwner,
+        NameOwner, SlicePatComponents,
     },
     AstNode, AstPtr, SyntaxNodePtr,
 };
@@ -25,7 +25,10 @@ use crate::{
     body::{Body, BodySourceMap, Expander, LabelSource, PatPtr, SyntheticSyntax},
     builtin_type::{BuiltinFloat, BuiltinInt, BuiltinUint},
     db::DefDatabase,
-    diagnostics::{InactiveCode, MacroError, UnresolvedMacroCall, UnresolvedProcMacro},
+    diagnostics::{
+        InactiveCode, InvalidAttributePlacement, MacroError, UnresolvedMacroCall,
+        UnresolvedProcMacro,
+    },
     expr::{
         dummy_expr_id, ArithOp, Array, BinaryOp, BindingAnnotation, CmpOp, Expr, ExprId, Label,
         LabelId, Literal, LogicOp, MatchArm, Ordering, Pat, PatId, RecordFieldPat, RecordLitField,
@@ -207,6 +210,7 @@ impl ExprCollector<'_> {
     fn maybe_collect_expr(&mut self, expr: ast::Expr) -> Option<ExprId> {
         let syntax_ptr = AstPtr::new(&expr);
         self.check_cfg(&expr)?;
+        self.check_attr_placement(&expr);
 
         Some(match expr {
             ast::Expr::IfExpr(e) => {
@@ -613,11 +617,11 @@ impl ExprCollector<'_> {
                 ));
             }
             Some(err) => {
-                self.source_map.diagnostics.push(BodyDiagnostic::MacroError(MacroError {
-                    file: outer_file,
-                    node: syntax_ptr.into(),
-                    message: err.to_string(),
-                }));
+                self.source_map.diagnostics.push(BodyDiagnostic::MacroError(MacroError::new(
+                    outer_file,
+                    syntax_ptr.into(),
+                    err,
+                )));
             }
             None => {}
         }
@@ -659,7 +663,14 @@ impl ExprCollector<'_> {
                 let type_ref =
                     stmt.ty().map(|it| Interned::new(TypeRef::from_ast(&self.ctx(), it)));
                 let initializer = stmt.initializer().map(|e| self.collect_expr(e));
-                self.statements_in_scope.push(Statement::Let { pat, type_ref, initializer });
+                let else_branch =
+                    stmt.let_else().and_then(|it| it.block_expr()).map(|e| self.collect_block(e));
+                self.statements_in_scope.push(Statement::Let {
+                    pat,
+                    type_ref,
+                    initializer,
+                    else_branch,
+                });
             }
             ast::Stmt::ExprStmt(stmt) => {
                 if self.check_cfg(&stmt).is_none() {
@@ -920,6 +931,29 @@ impl ExprCollector<'_> {
         (args, ellipsis)
     }
 
+    /// Emits diagnostics for the attributes on `expr` that aren't allowed on expressions.
+    fn check_attr_placement(&mut self, expr: &ast::Expr) {
+        const ALLOWED: &[&str] = &["cfg", "cfg_attr", "allow", "warn", "deny", "forbid"];
+
+        for attr in expr.attrs() {
+            // Tool attributes like `#[rustfmt::skip]` have more than one segment and are fine.
+            let name = match attr.simple_name() {
+                Some(name) => name,
+                None => continue,
+            };
+            if ALLOWED.contains(&name.as_str()) {
+                continue;
+            }
+            self.source_map.diagnostics.push(BodyDiagnostic::InvalidAttributePlacement(
+                InvalidAttributePlacement {
+                    file: self.expander.current_file_id,
+                    node: AstPtr::new(&attr),
+                    attr: name.to_string(),
+                },
+            ));
+        }
+    }
+
     /// Returns `None` (and emits diagnostics) when `owner` if `#[cfg]`d out, and `Some(())` when
     /// not.
     fn check_cfg(&mut self, owner: &dyn ast::AttrsOwner) -> Option<()> {
@@ -3,7 +3,7 @@ mod block;
 use base_db::{fixture::WithFixture, SourceDatabase};
 use expect_test::Expect;
 
-use crate::{test_db::TestDB, ModuleDefId};
+use crate::{diagnostics::MacroError, test_db::TestDB, ModuleDefId};
 
 use super::*;
 
@@ -121,6 +121,22 @@ fn f() {
     );
 }
 
+#[test]
+fn invalid_attribute_placement() {
+    check_diagnostics(
+        r"
+fn f() {
+    let x = #[inline] 0;
+          //^^^^^^^^^ `#[inline]` is not allowed on expressions
+    let y = #[allow(unused)] 1;
+    let z = #[rustfmt::skip] 2;
+    g(#[must_use] x);
+    //^^^^^^^^^^^ `#[must_use]` is not allowed on expressions
+}
+    ",
+    );
+}
+
 #[test]
 fn macro_diag_builtin() {
     check_diagnostics(
@@ -194,6 +210,64 @@ fn f() {
     );
 }
 
+#[test]
+fn macro_error_message_id() {
+    let db: TestDB = TestDB::with_files(
+        r#"
+#[rustc_builtin_macro]
+macro_rules! compile_error {}
+
+fn f() {
+    compile_error!("oops");
+}
+"#,
+    );
+    let mut errors = Vec::new();
+    db.diagnostics(|d| {
+        if let Some(d) = d.as_any().downcast_ref::<MacroError>() {
+            errors.push((d.message_id, d.params.clone()));
+        }
+    });
+    assert_eq!(errors, [("other", vec!["oops".to_string()])]);
+}
+
+#[test]
+fn overflowing_literal() {
+    check_diagnostics(
+        r#"
+        const A: u8 = 255;
+        const B: u8 = 256;
+                    //^^^ literal out of range for `u8`
+        const C: i8 = -128;
+        const D: i8 = -129;
+                    //^^^^ literal out of range for `i8`
+        static E: u16 = 0x1_0000;
+                      //^^^^^^^^ literal out of range for `u16`
+        static F: u32 = 0x1_0000;
+        const G: u8 = 256u16;
+        const H: u128 = 340282366920938463463374607431768211455;
+        const _: i64 = 1 << 63;
+        "#,
+    );
+}
+
+#[test]
+fn overflowing_literal_of_user_type() {
+    check_diagnostics(
+        r#"
+        mod m {
+            #[allow(non_camel_case_types)]
+            type u8 = u16;
+            const A: u8 = 256;
+        }
+        mod n {
+            const B: u8 = 256;
+                        //^^^ literal out of range for `u8`
+        }
+        "#,
+    );
+}
+
 #[test]
 fn dollar_crate_in_builtin_macro() {
     check_diagnostics(
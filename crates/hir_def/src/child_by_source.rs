@@ -4,7 +4,11 @@
 //! This modules allows one to go in the opposite direction: start with a syntax
 //! node for a *child*, and get its hir.
 
+use std::sync::Arc;
+
+use base_db::FileId;
 use either::Either;
+use hir_expand::InFile;
 
 use crate::{
     db::DefDatabase,
@@ -71,9 +75,21 @@ impl ChildBySource for ImplId {
 
 impl ChildBySource for ModuleId {
     fn child_by_source_to(&self, db: &dyn DefDatabase, res: &mut DynMap) {
+        #[cfg(any(test, feature = "metrics"))]
+        let start = metrics::is_enabled().then(|| (std::time::Instant::now(), res.len()));
+
         let def_map = self.def_map(db);
         let module_data = &def_map[self.local_id];
         module_data.scope.child_by_source_to(db, res);
+
+        #[cfg(any(test, feature = "metrics"))]
+        if let Some((start, len)) = start {
+            metrics::report(metrics::ModuleMetrics {
+                module: *self,
+                duration: start.elapsed(),
+                entries: res.len() - len,
+            });
+        }
     }
 }
 
@@ -122,6 +138,12 @@ impl ChildBySource for ItemScope {
                         map[keys::ENUM].insert(src, enum_)
                     }
                 },
+                ModuleDefId::ModuleId(module) => {
+                    let def_map = module.def_map(db);
+                    if let Some(src) = def_map[module.local_id].declaration_source(db) {
+                        map[keys::MODULE].insert(src, module)
+                    }
+                }
                 _ => (),
             }
         }
@@ -166,8 +188,290 @@ impl ChildBySource for DefWithBodyId {
         let body = db.body(*self);
         for (_, def_map) in body.blocks(db) {
             // All block expressions are merged into the same map, because they logically all add
-            // inner items to the containing `DefWithBodyId`.
+            // inner items to the containing `DefWithBodyId`. This includes the blocks of closures,
+            // which are lowered as part of the enclosing body.
             def_map[def_map.root()].scope.child_by_source_to(db, res);
         }
     }
 }
+
+/// Opt-in measurements of how expensive `child_by_source` is for each module, to help find out why
+/// indexing is slow.
+#[cfg(any(test, feature = "metrics"))]
+pub mod metrics {
+    use std::{cell::RefCell, time::Duration};
+
+    use crate::ModuleId;
+
+    #[derive(Debug, Clone)]
+    pub struct ModuleMetrics {
+        pub module: ModuleId,
+        pub duration: Duration,
+        /// The number of entries `child_by_source` added for the module.
+        pub entries: usize,
+    }
+
+    type Sink = Box<dyn FnMut(ModuleMetrics)>;
+
+    thread_local! {
+        static SINK: RefCell<Option<Sink>> = RefCell::new(None);
+    }
+
+    /// Reports the metrics of every module that is mapped on this thread to `sink`, until the
+    /// returned guard is dropped.
+    pub fn record(sink: impl FnMut(ModuleMetrics) + 'static) -> RecordGuard {
+        SINK.with(|it| *it.borrow_mut() = Some(Box::new(sink)));
+        RecordGuard { _private: () }
+    }
+
+    #[must_use]
+    pub struct RecordGuard {
+        _private: (),
+    }
+
+    impl Drop for RecordGuard {
+        fn drop(&mut self) {
+            SINK.with(|it| *it.borrow_mut() = None);
+        }
+    }
+
+    pub(super) fn is_enabled() -> bool {
+        SINK.with(|it| it.borrow().is_some())
+    }
+
+    pub(super) fn report(metrics: ModuleMetrics) {
+        SINK.with(|it| {
+            if let Some(sink) = &mut *it.borrow_mut() {
+                sink(metrics);
+            }
+        });
+    }
+}
+
+pub(crate) fn items_in_file_query(db: &dyn DefDatabase, file_id: FileId) -> Arc<[ModuleDefId]> {
+    // Like `Semantics`, use the first module that the file is the root of.
+    let module = db.relevant_crates(file_id).iter().find_map(|&krate| {
+        let def_map = db.crate_def_map(krate);
+        let local_id = def_map.modules_for_file(file_id).next()?;
+        Some(def_map.module_id(local_id))
+    });
+    let map = match module {
+        Some(it) => it.child_by_source(db),
+        None => return Arc::new([]),
+    };
+
+    let mut items = Vec::new();
+    macro_rules! collect_items {
+        ($($key:ident),* $(,)?) => {$(
+            for (ptr, &id) in map[keys::$key].ptrs() {
+                items.push((ptr.map(|it| it.syntax_node_ptr()), ModuleDefId::from(id)));
+            }
+        )*};
+    }
+    collect_items![FUNCTION, CONST, STATIC, TYPE_ALIAS, TRAIT, STRUCT, UNION, ENUM, MODULE];
+
+    // Items from macro expansions are placed at the macro call they come from.
+    let mut items: Vec<_> = items
+        .into_iter()
+        .filter_map(|(ptr, id)| {
+            let root = db.parse_or_expand(ptr.file_id)?;
+            let node = ptr.value.to_node(&root);
+            let range = InFile::new(ptr.file_id, &node).original_file_range(db.upcast());
+            if range.file_id != file_id {
+                return None;
+            }
+            Some((range.range.start(), id))
+        })
+        .collect();
+    items.sort_by_key(|&(start, _)| start);
+    items.dedup();
+    items.into_iter().map(|(_, id)| id).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use base_db::{fixture::WithFixture, SourceDatabase};
+    use hir_expand::{hygiene::Hygiene, InFile};
+    use syntax::{
+        algo::find_node_at_offset,
+        ast::{self, TypeBoundsOwner},
+        AstNode,
+    };
+
+    use crate::{
+        path::ModPath,
+        resolver::{HasResolver, TypeNs},
+        test_db::TestDB,
+        AdtId, FunctionId, GenericDefId,
+    };
+
+    use super::*;
+
+    /// Returns the `N` at the `$0` marker in `ra_fixture`, together with the function containing it.
+    fn function_and_node_at_cursor<N: AstNode>(
+        ra_fixture: &str,
+    ) -> (TestDB, FunctionId, InFile<N>) {
+        let (db, position) = TestDB::with_position(ra_fixture);
+        let file = db.parse(position.file_id).tree();
+        let node: N = find_node_at_offset(file.syntax(), position.offset).unwrap();
+        let func = node.syntax().ancestors().find_map(ast::Fn::cast).unwrap();
+        let module = db.module_for_file(position.file_id);
+        let func = *module.child_by_source(&db)[keys::FUNCTION]
+            .get(&InFile::new(position.file_id.into(), func))
+            .unwrap();
+        (db, func, InFile::new(position.file_id.into(), node))
+    }
+
+    #[test]
+    fn where_clause_bound() {
+        let (db, func, pred) = function_and_node_at_cursor::<ast::WherePred>(
+            r#"
+trait Clone {}
+fn f<T>() where T: Cl$0one {}
+"#,
+        );
+        let def = GenericDefId::from(func);
+        let owner = *def.child_by_source(&db)[keys::WHERE_PREDICATE].get(&pred).unwrap();
+        assert_eq!(owner, def);
+
+        let path = match pred.value.type_bound_list().unwrap().bounds().next().unwrap().ty() {
+            Some(ast::Type::PathType(it)) => it.path().unwrap(),
+            _ => panic!("expected a path bound"),
+        };
+        let path = ModPath::from_src(path, &Hygiene::new_unhygienic()).unwrap();
+        match owner.resolver(&db).resolve_path_in_type_ns_fully(&db, &path) {
+            Some(TypeNs::TraitId(it)) => assert_eq!(db.trait_data(it).name.to_string(), "Clone"),
+            resolved => panic!("expected a trait, got {:?}", resolved),
+        }
+    }
+
+    #[test]
+    fn dyn_trait_in_signature() {
+        let (db, func, dyn_trait) = function_and_node_at_cursor::<ast::DynTraitType>(
+            r#"
+struct Box<T: ?Sized>(*const T);
+trait MyTrait {}
+fn f(_: Box<dyn My$0Trait>) {
+    let _: Box<dyn MyTrait>;
+}
+"#,
+        );
+        let map = GenericDefId::from(func).child_by_source(&db);
+        let trait_ = *map[keys::DYN_TRAIT].get(&dyn_trait).unwrap();
+        assert_eq!(db.trait_data(trait_).name.to_string(), "MyTrait");
+        // The `dyn MyTrait` in the body belongs to the body, not to the signature.
+        assert_eq!(map[keys::DYN_TRAIT].len(), 1);
+    }
+
+    #[test]
+    fn module_metrics() {
+        let (db, file_id) = TestDB::with_single_file(
+            r#"
+struct S;
+fn f() {}
+"#,
+        );
+        let module = db.module_for_file(file_id);
+
+        let recorded = Rc::new(RefCell::new(Vec::new()));
+        let guard = metrics::record({
+            let recorded = recorded.clone();
+            move |it| recorded.borrow_mut().push(it)
+        });
+        let map = module.child_by_source(&db);
+        drop(guard);
+        module.child_by_source(&db);
+
+        let recorded = recorded.borrow();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].module, module);
+        // `S` and `f`.
+        assert_eq!(recorded[0].entries, 2);
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn present_keys() {
+        let (db, file_id) = TestDB::with_single_file(
+            r#"
+struct S;
+impl S {}
+fn f() {}
+"#,
+        );
+        let module = db.module_for_file(file_id);
+        let keys = module.child_by_source(&db).present_keys();
+        assert!(keys.contains(&"FUNCTION"));
+        assert!(keys.contains(&"IMPL"));
+        assert!(!keys.contains(&"TRAIT"));
+    }
+
+    #[test]
+    fn items_in_file() {
+        let (db, file_id) = TestDB::with_single_file(
+            r#"
+macro_rules! make { () => { fn made() {} } }
+struct Zeta;
+fn alpha() {}
+impl Zeta {}
+make!();
+mod inner {
+    fn nested() {}
+}
+const C: () = ();
+"#,
+        );
+        let names = db
+            .items_in_file(file_id)
+            .iter()
+            .map(|&item| match item {
+                ModuleDefId::FunctionId(it) => db.function_data(it).name.to_string(),
+                ModuleDefId::AdtId(AdtId::StructId(it)) => db.struct_data(it).name.to_string(),
+                ModuleDefId::ConstId(it) => db.const_data(it).name.as_ref().unwrap().to_string(),
+                ModuleDefId::ModuleId(it) => {
+                    let def_map = it.def_map(&db);
+                    def_map[it.local_id]
+                        .parent
+                        .and_then(|parent| {
+                            def_map[parent]
+                                .children
+                                .iter()
+                                .find(|(_, &child)| child == it.local_id)
+                                .map(|(name, _)| name.to_string())
+                        })
+                        .unwrap()
+                }
+                item => panic!("unexpected item {:?}", item),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Zeta", "alpha", "made", "inner", "C"]);
+    }
+
+    #[test]
+    fn deterministic_order() {
+        let fixture = r#"
+fn c() {}
+fn a() {}
+fn b() {}
+"#;
+        let function_names = || {
+            let (db, file_id) = TestDB::with_single_file(fixture);
+            let module = db.module_for_file(file_id);
+            let map = module.child_by_source(&db);
+            assert_eq!(
+                map[keys::FUNCTION].values(),
+                module.child_by_source(&db)[keys::FUNCTION].values()
+            );
+            map[keys::FUNCTION]
+                .values()
+                .into_iter()
+                .map(|&func| db.function_data(func).name.to_string())
+                .collect::<Vec<_>>()
+        };
+        let names = function_names();
+        assert_eq!(names, ["c", "a", "b"]);
+        assert_eq!(names, function_names());
+    }
+}
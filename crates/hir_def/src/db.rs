@@ -1,10 +1,11 @@
 //! Defines database & queries for name resolution.
 use std::sync::Arc;
 
-use base_db::{salsa, CrateId, SourceDatabase, Upcast};
+use base_db::{salsa, CrateId, FileId, SourceDatabase, Upcast};
 use either::Either;
-use hir_expand::{db::AstDatabase, HirFileId};
+use hir_expand::{db::AstDatabase, name::Name, HirFileId, MacroCallKind, MacroDefId};
 use la_arena::ArenaMap;
+use rustc_hash::FxHashMap;
 use syntax::{ast, AstPtr, SmolStr};
 
 use crate::{
@@ -17,12 +18,12 @@ use crate::{
     intern::Interned,
     item_tree::ItemTree,
     lang_item::{LangItemTarget, LangItems},
-    nameres::DefMap,
+    nameres::{DefDiagnostic, DefMap, MacroExpansionStats},
     visibility::{self, Visibility},
     AttrDefId, BlockId, BlockLoc, ConstId, ConstLoc, DefWithBodyId, EnumId, EnumLoc, FunctionId,
-    FunctionLoc, GenericDefId, ImplId, ImplLoc, LocalEnumVariantId, LocalFieldId, StaticId,
-    StaticLoc, StructId, StructLoc, TraitId, TraitLoc, TypeAliasId, TypeAliasLoc, UnionId,
-    UnionLoc, VariantId,
+    FunctionLoc, GenericDefId, ImplId, ImplLoc, LocalEnumVariantId, LocalFieldId, ModuleDefId,
+    ModuleId, StaticId, StaticLoc, StructId, StructLoc, TraitId, TraitLoc, TypeAliasId,
+    TypeAliasLoc, UnionId, UnionLoc, VariantId,
 };
 
 #[salsa::query_group(InternDatabaseStorage)]
@@ -79,6 +80,41 @@ pub trait DefDatabase: InternDatabase + AstDatabase + Upcast<dyn AstDatabase> {
     #[salsa::invoke(DefMap::block_def_map_query)]
     fn block_def_map(&self, block: BlockId) -> Option<Arc<DefMap>>;
 
+    /// Returns the submodules of `module`, in the order they are declared in.
+    #[salsa::invoke(DefMap::module_children_query)]
+    fn module_children(&self, module: ModuleId) -> Arc<[ModuleId]>;
+
+    /// Returns the macro calls in `module` that name resolution resolved, including derives,
+    /// together with the macros they resolved to. Calls in function bodies are not included.
+    #[salsa::invoke(DefMap::all_macro_calls_query)]
+    fn all_macro_calls(&self, module: ModuleId) -> Arc<[(MacroCallKind, MacroDefId)]>;
+
+    /// Returns the number of macro calls that name resolution expanded in `krate` and the size of
+    /// their expansions. Calls in function bodies are not included.
+    #[salsa::invoke(DefMap::macro_expansion_stats_query)]
+    fn macro_expansion_stats(&self, krate: CrateId) -> MacroExpansionStats;
+
+    /// Returns the items that `module` re-exports with `use`, sorted by the name they are
+    /// re-exported under, together with the items they originally refer to. Only types and values
+    /// are included.
+    #[salsa::invoke(DefMap::reexports_query)]
+    fn reexports(&self, module: ModuleId) -> Arc<[(Name, ModuleDefId)]>;
+
+    /// Returns the items declared in the modules of `krate` by their crate-relative path, like
+    /// `crate::a::Foo`. Items declared in function bodies have no such path and are left out.
+    #[salsa::invoke(DefMap::canonical_paths_query)]
+    fn canonical_paths(&self, krate: CrateId) -> Arc<FxHashMap<String, ModuleDefId>>;
+
+    /// Returns the items declared at the top level of `file_id`, ordered by their position in the
+    /// file. Items that macro calls expand to are placed at the macro call.
+    #[salsa::invoke(crate::child_by_source::items_in_file_query)]
+    fn items_in_file(&self, file_id: FileId) -> Arc<[ModuleDefId]>;
+
+    /// Returns the name resolution diagnostics of all modules defined in `file_id`, including
+    /// inline modules, that point into that file.
+    #[salsa::invoke(crate::nameres::diagnostics_affecting_file_query)]
+    fn diagnostics_affecting_file(&self, file_id: FileId) -> Arc<[DefDiagnostic]>;
+
     #[salsa::invoke(StructData::struct_data_query)]
     fn struct_data(&self, id: StructId) -> Arc<StructData>;
     #[salsa::invoke(StructData::union_data_query)]
@@ -1,18 +1,448 @@
 //! Diagnostics produced by `hir_def`.
 
-use std::any::Any;
+use std::{any::Any, iter};
 use stdx::format_to;
 
 use cfg::{CfgExpr, CfgOptions, DnfExpr};
+use hir_expand::ast_id_map::FileAstId;
 use hir_expand::diagnostics::{Diagnostic, DiagnosticCode, DiagnosticSink};
-use hir_expand::{HirFileId, InFile};
-use syntax::{ast, AstPtr, SyntaxNodePtr, TextRange};
+use hir_expand::{ExpandError, HirFileId, InFile};
+use rustc_hash::FxHashSet;
+use syntax::{
+    ast::{self, PathSegmentKind},
+    AstNode, AstPtr, SyntaxKind, SyntaxNode, SyntaxNodePtr, TextRange,
+};
 
-use crate::{db::DefDatabase, path::ModPath, DefWithBodyId};
+use crate::{
+    adt::VariantData,
+    body::{Body, BodySourceMap},
+    builtin_type::{BuiltinInt, BuiltinType, BuiltinUint},
+    db::DefDatabase,
+    intern::Interned,
+    item_scope::ItemScope,
+    item_tree::{Import, ItemTree, ItemTreeId, ItemTreeNode, ModItem, ModKind},
+    nameres::{DefDiagnostic, DefMap, ModuleOrigin, ModuleSource},
+    path::{GenericArg, ImportAlias, ModPath},
+    resolver::{HasResolver, Resolver, TypeNs},
+    type_ref::TypeRef,
+    visibility::RawVisibility,
+    AdtId, AssocItemId, AstId, DefWithBodyId, GenericDefId, HasModule, ImplId, LocalModuleId,
+    Lookup, ModuleDefId, ModuleId, TraitId,
+};
 
 pub fn validate_body(db: &dyn DefDatabase, owner: DefWithBodyId, sink: &mut DiagnosticSink<'_>) {
-    let source_map = db.body_with_source_map(owner).1;
+    let (body, source_map) = db.body_with_source_map(owner);
     source_map.add_diagnostics(db, sink);
+    // Items in block expressions are collected into their own `DefMap`s, which aren't reachable
+    // from the modules of the crate.
+    for (_, def_map) in body.blocks(db) {
+        def_map.add_block_diagnostics(db, sink);
+    }
+
+    let (ty, ast) = match owner {
+        DefWithBodyId::ConstId(it) => {
+            (db.const_data(it).type_ref.clone(), item_ast_id(db, it.lookup(db).id))
+        }
+        DefWithBodyId::StaticId(it) => {
+            (db.static_data(it).type_ref.clone(), item_ast_id(db, it.lookup(db).id))
+        }
+        DefWithBodyId::FunctionId(_) => return,
+    };
+    validate_initializer(db, owner, &body, &source_map, &ty, ast, sink);
+}
+
+/// Validates the signature of a module-level or associated item. This needs names to be resolved,
+/// so it can't happen while the `DefMap` is being collected.
+pub fn validate_module_item(
+    db: &dyn DefDatabase,
+    item: ModuleDefId,
+    sink: &mut DiagnosticSink<'_>,
+) {
+    let field_types =
+        |data: &VariantData| data.fields().iter().map(|(_, it)| it.type_ref.clone()).collect();
+    let (types, resolver, ast): (Vec<_>, _, _) = match item {
+        ModuleDefId::FunctionId(it) => {
+            let data = db.function_data(it);
+            let types = data.params.iter().chain(iter::once(&data.ret_type)).cloned().collect();
+            (types, it.resolver(db), item_ast_id(db, it.lookup(db).id))
+        }
+        ModuleDefId::AdtId(AdtId::StructId(it)) => (
+            field_types(&db.struct_data(it).variant_data),
+            it.resolver(db),
+            item_ast_id(db, it.lookup(db).id),
+        ),
+        ModuleDefId::AdtId(AdtId::UnionId(it)) => (
+            field_types(&db.union_data(it).variant_data),
+            it.resolver(db),
+            item_ast_id(db, it.lookup(db).id),
+        ),
+        ModuleDefId::ConstId(it) => (
+            vec![db.const_data(it).type_ref.clone()],
+            it.resolver(db),
+            item_ast_id(db, it.lookup(db).id),
+        ),
+        ModuleDefId::StaticId(it) => (
+            vec![db.static_data(it).type_ref.clone()],
+            it.resolver(db),
+            item_ast_id(db, it.lookup(db).id),
+        ),
+        ModuleDefId::TypeAliasId(it) => (
+            db.type_alias_data(it).type_ref.iter().cloned().collect(),
+            it.resolver(db),
+            item_ast_id(db, it.lookup(db).id),
+        ),
+        _ => return,
+    };
+    validate_generic_args(db, &resolver, ast, &types, sink);
+}
+
+/// Validates the header of an `impl` block, see [`validate_module_item`].
+pub fn validate_impl(db: &dyn DefDatabase, impl_id: ImplId, sink: &mut DiagnosticSink<'_>) {
+    let types = [db.impl_data(impl_id).self_ty.clone()];
+    let ast = item_ast_id(db, impl_id.lookup(db).id);
+    validate_generic_args(db, &impl_id.resolver(db), ast, &types, sink);
+}
+
+/// Reports paths in `types` that pass more type arguments than the ADT or type alias they
+/// resolve to has type parameters.
+fn validate_generic_args(
+    db: &dyn DefDatabase,
+    resolver: &Resolver,
+    ast: AstId<ast::Item>,
+    types: &[Interned<TypeRef>],
+    sink: &mut DiagnosticSink<'_>,
+) {
+    let module = match resolver.module() {
+        Some(it) => it.local_id,
+        None => return,
+    };
+    for type_ref in types {
+        type_ref.walk(&mut |type_ref| {
+            let path = match type_ref {
+                TypeRef::Path(path) if path.type_anchor().is_none() => path,
+                _ => return,
+            };
+            let args = match path.segments().last().and_then(|it| it.args_and_bindings) {
+                Some(it) if !it.has_self_type => it,
+                _ => return,
+            };
+            let def: GenericDefId = match resolver.resolve_path_in_type_ns(db, path.mod_path()) {
+                Some((TypeNs::AdtId(it), None)) => it.into(),
+                Some((TypeNs::TypeAliasId(it), None)) => it.into(),
+                _ => return,
+            };
+            let params = db.generic_params(def);
+            if !params.consts.is_empty() {
+                return;
+            }
+            let expected = params.types.len();
+            let found = args.args.iter().filter(|it| matches!(it, GenericArg::Type(_))).count();
+            if found > expected {
+                let path = path.mod_path().clone();
+                DefDiagnostic::wrong_number_of_generic_args(module, ast, path, expected, found)
+                    .add_to_sink(db, sink);
+            }
+        });
+    }
+}
+
+fn item_ast_id<N: ItemTreeNode>(db: &dyn DefDatabase, id: ItemTreeId<N>) -> AstId<ast::Item> {
+    InFile::new(id.file_id(), id.item_tree(db)[id.value].ast_id().upcast())
+}
+
+/// Reports the private imports of `module` that are never used. An import is used if its name is
+/// mentioned in the module or one of its descendants, including the expansions of the macro calls
+/// in them, or if it imports one of `used_traits`, the traits whose methods or associated items
+/// the bodies of these modules use without naming them.
+///
+/// Names are looked up in the syntax rather than resolved, so an import also counts as used if
+/// something else of the same name is mentioned. Imports in block expressions and macro expansions
+/// aren't checked.
+pub fn validate_imports(
+    db: &dyn DefDatabase,
+    module: ModuleId,
+    used_traits: &FxHashSet<TraitId>,
+    sink: &mut DiagnosticSink<'_>,
+) {
+    let def_map = module.def_map(db);
+    let scope = &def_map[module.local_id].scope;
+    let candidates: Vec<_> = module_imports(db, &def_map, module.local_id)
+        .into_iter()
+        .filter_map(|id| {
+            let item_tree = id.item_tree(db);
+            let import = &item_tree[id.value];
+            if import.is_glob || item_tree[import.visibility] != RawVisibility::private() {
+                return None;
+            }
+            let name = match &import.alias {
+                Some(ImportAlias::Alias(name)) => name.clone(),
+                Some(ImportAlias::Underscore) => return None,
+                None => import.path.segments().last()?.clone(),
+            };
+            // Unresolved imports are reported as such.
+            if scope.get(&name).is_none() {
+                return None;
+            }
+            Some((id, import.index, name))
+        })
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+
+    // Private imports are visible in descendant modules too, which may refer to them through
+    // `super::`, `crate::` or glob imports. Inline modules are part of their parent's syntax.
+    let mut names = FxHashSet::default();
+    let mut modules = vec![module.local_id];
+    while let Some(it) = modules.pop() {
+        modules.extend(def_map[it].children.values().copied());
+        if it == module.local_id || !def_map[it].origin.is_inline() {
+            collect_referred_names(&module_syntax(db, &def_map, it), &mut names);
+        }
+        let expansions = def_map[it].scope.macro_invocations().map(|call| call.as_file()).chain(
+            module_bodies(db, &def_map[it].scope).into_iter().flat_map(|body| {
+                db.body_with_source_map(body).1.expansion_files().collect::<Vec<_>>()
+            }),
+        );
+        for file_id in expansions {
+            if let Some(syntax) = db.parse_or_expand(file_id) {
+                collect_referred_names(&syntax, &mut names);
+            }
+        }
+    }
+
+    for (id, index, name) in candidates {
+        if names.contains(&name.to_string()) {
+            continue;
+        }
+        if let Some(ModuleDefId::TraitId(it)) = scope.get(&name).take_types() {
+            if used_traits.contains(&it) {
+                continue;
+            }
+        }
+        DefDiagnostic::unused_import(module.local_id, id, index).add_to_sink(db, sink);
+    }
+}
+
+/// Returns the items with bodies that are declared in `scope`, including associated items.
+fn module_bodies(db: &dyn DefDatabase, scope: &ItemScope) -> Vec<DefWithBodyId> {
+    let mut assoc_items = Vec::new();
+    let mut bodies = Vec::new();
+    for decl in scope.declarations() {
+        match decl {
+            ModuleDefId::FunctionId(it) => bodies.push(it.into()),
+            ModuleDefId::ConstId(it) => bodies.push(it.into()),
+            ModuleDefId::StaticId(it) => bodies.push(it.into()),
+            ModuleDefId::TraitId(it) => {
+                assoc_items.extend(db.trait_data(it).items.iter().map(|(_, item)| *item))
+            }
+            _ => {}
+        }
+    }
+    for impl_id in scope.impls() {
+        assoc_items.extend(db.impl_data(impl_id).items.iter().copied());
+    }
+    bodies.extend(assoc_items.into_iter().filter_map(|item| match item {
+        AssocItemId::FunctionId(it) => Some(DefWithBodyId::from(it)),
+        AssocItemId::ConstId(it) => Some(it.into()),
+        AssocItemId::TypeAliasId(_) => None,
+    }));
+    bodies
+}
+
+/// Returns the imports written directly in `module`.
+fn module_imports(
+    db: &dyn DefDatabase,
+    def_map: &DefMap,
+    module: LocalModuleId,
+) -> Vec<ItemTreeId<Import>> {
+    let (file_id, item_tree) = match def_map[module].origin {
+        ModuleOrigin::CrateRoot { definition } | ModuleOrigin::File { definition, .. } => {
+            (definition.into(), db.file_item_tree(definition.into()))
+        }
+        ModuleOrigin::Inline { definition } => {
+            (definition.file_id, db.file_item_tree(definition.file_id))
+        }
+        ModuleOrigin::BlockExpr { .. } => return Vec::new(),
+    };
+    let items = match def_map[module].origin {
+        ModuleOrigin::Inline { definition } => {
+            match inline_module_items(&item_tree, item_tree.top_level_items(), definition.value) {
+                Some(it) => it,
+                None => return Vec::new(),
+            }
+        }
+        _ => item_tree.top_level_items(),
+    };
+    items
+        .iter()
+        .filter_map(|item| match item {
+            ModItem::Import(it) => Some(ItemTreeId::new(file_id, *it)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn inline_module_items<'a>(
+    item_tree: &'a ItemTree,
+    items: &'a [ModItem],
+    ast_id: FileAstId<ast::Module>,
+) -> Option<&'a [ModItem]> {
+    items.iter().find_map(|item| {
+        let module = match item {
+            ModItem::Mod(it) => &item_tree[*it],
+            _ => return None,
+        };
+        match &module.kind {
+            ModKind::Inline { items } if module.ast_id == ast_id => Some(&**items),
+            ModKind::Inline { items } => inline_module_items(item_tree, items, ast_id),
+            ModKind::Outline {} => None,
+        }
+    })
+}
+
+fn module_syntax(db: &dyn DefDatabase, def_map: &DefMap, module: LocalModuleId) -> SyntaxNode {
+    match def_map[module].definition_source(db).value {
+        ModuleSource::SourceFile(it) => it.syntax().clone(),
+        ModuleSource::Module(it) => it.syntax().clone(),
+        ModuleSource::BlockExpr(it) => it.syntax().clone(),
+    }
+}
+
+/// Collects the names in `syntax` that may refer to an import: the first segments of paths, names
+/// qualified with `self::`, `super::` or `crate::` and identifiers in token trees, like macro
+/// arguments. Unqualified paths in `use` items only count if they lead to another item, as in
+/// `use Name::Item;`.
+fn collect_referred_names(syntax: &SyntaxNode, names: &mut FxHashSet<String>) {
+    for token in syntax.descendants_with_tokens().filter_map(|it| it.into_token()) {
+        if token.kind() != SyntaxKind::IDENT {
+            continue;
+        }
+        let parent = match token.parent() {
+            Some(it) => it,
+            None => continue,
+        };
+        let referred = if parent.kind() == SyntaxKind::TOKEN_TREE {
+            true
+        } else {
+            match parent.parent().and_then(ast::PathSegment::cast) {
+                Some(segment) => is_import_reference(&segment.parent_path()),
+                None => false,
+            }
+        };
+        if referred {
+            names.insert(token.text().to_string());
+        }
+    }
+}
+
+fn is_import_reference(path: &ast::Path) -> bool {
+    if let Some(qualifier) = path.qualifier() {
+        // Descendant modules can refer to the import with paths like `super::super::Name`.
+        return iter::successors(Some(qualifier), |it| it.qualifier()).all(|it| {
+            matches!(
+                it.segment().and_then(|it| it.kind()),
+                Some(PathSegmentKind::SelfKw)
+                    | Some(PathSegmentKind::SuperKw)
+                    | Some(PathSegmentKind::CrateKw)
+            )
+        });
+    }
+    let tree = match path.syntax().ancestors().find_map(ast::UseTree::cast) {
+        Some(it) => it,
+        None => return true,
+    };
+    // Only the start of a top-level use tree can refer to an import, and only if the tree doesn't
+    // end there.
+    let is_top_level = tree.syntax().parent().map(|it| it.kind()) == Some(SyntaxKind::USE);
+    is_top_level && (path.parent_path().is_some() || tree.use_tree_list().is_some())
+}
+
+/// Reports the initializer of a `const` or `static` of a primitive integer type if it is a
+/// literal that doesn't fit into that type.
+fn validate_initializer(
+    db: &dyn DefDatabase,
+    owner: DefWithBodyId,
+    body: &Body,
+    source_map: &BodySourceMap,
+    ty: &TypeRef,
+    ast: AstId<ast::Item>,
+    sink: &mut DiagnosticSink<'_>,
+) {
+    let path = match ty {
+        TypeRef::Path(path) => path,
+        _ => return,
+    };
+    // Resolve the type instead of looking at its name, a user-defined `u8` isn't a primitive.
+    let ty = match owner.resolver(db).resolve_path_in_type_ns_fully(db, path.mod_path()) {
+        Some(TypeNs::BuiltinType(it @ BuiltinType::Int(_)))
+        | Some(TypeNs::BuiltinType(it @ BuiltinType::Uint(_))) => it,
+        _ => return,
+    };
+    let source = match source_map.expr_syntax(body.body_expr) {
+        Ok(it) => it,
+        Err(_) => return,
+    };
+    let root = source.file_syntax(db.upcast());
+    if literal_overflows(&source.value.to_node(&root), ty) {
+        let module = owner.module(db).local_id;
+        DefDiagnostic::overflowing_literal(module, ast, ty.to_string()).add_to_sink(db, sink);
+    }
+}
+
+/// Checks whether `expr` is an integer literal, possibly negated, that doesn't fit into the
+/// primitive integer type `ty`. `isize` and `usize` are assumed to be 64 bits wide.
+fn literal_overflows(expr: &ast::Expr, ty: BuiltinType) -> bool {
+    let (negated, literal) = match expr {
+        ast::Expr::Literal(it) => (false, it.clone()),
+        ast::Expr::PrefixExpr(it) if it.op_kind() == Some(ast::PrefixOp::Neg) => match it.expr() {
+            Some(ast::Expr::Literal(it)) => (true, it),
+            _ => return false,
+        },
+        _ => return false,
+    };
+    let lit = match literal.kind() {
+        ast::LiteralKind::IntNumber(it) => it,
+        _ => return false,
+    };
+    match lit.suffix() {
+        Some(suffix) if suffix != ty.to_string() => return false,
+        _ => {}
+    }
+    let value = match lit.value() {
+        Some(it) => it,
+        None => return false,
+    };
+
+    let bits = |bits: u32| if bits == 128 { u128::MAX } else { (1u128 << bits) - 1 };
+    let (max, min) = match ty {
+        BuiltinType::Int(int) => {
+            let width = match int {
+                BuiltinInt::I8 => 8,
+                BuiltinInt::I16 => 16,
+                BuiltinInt::I32 => 32,
+                BuiltinInt::I64 | BuiltinInt::Isize => 64,
+                BuiltinInt::I128 => 128,
+            };
+            (bits(width - 1), bits(width - 1) + 1)
+        }
+        BuiltinType::Uint(uint) => {
+            let width = match uint {
+                BuiltinUint::U8 => 8,
+                BuiltinUint::U16 => 16,
+                BuiltinUint::U32 => 32,
+                BuiltinUint::U64 | BuiltinUint::Usize => 64,
+                BuiltinUint::U128 => 128,
+            };
+            (bits(width), 0)
+        }
+        _ => return false,
+    };
+    if negated {
+        value > min
+    } else {
+        value > max
+    }
 }
 
 // Diagnostic: unresolved-module
@@ -40,6 +470,59 @@ impl Diagnostic for UnresolvedModule {
     }
 }
 
+// Diagnostic: module-inline-and-file
+//
+// This diagnostic is triggered if an inline module like `mod foo { ... }` also has a file that an
+// out-of-line `mod foo;` would load. The file is ignored, which is likely a mistake.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ModuleInlineAndFile {
+    pub file: HirFileId,
+    pub decl: AstPtr<ast::Module>,
+    pub path: String,
+}
+
+impl Diagnostic for ModuleInlineAndFile {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("module-inline-and-file")
+    }
+    fn message(&self) -> String {
+        format!("module is defined inline, so `{}` is ignored", self.path)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.decl.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: non-portable-path
+//
+// This diagnostic is shown for `#[path]` attributes that separate directories with backslashes,
+// like `#[path = "a\\b.rs"]`. Only Windows treats them as separators, so the module isn't found on
+// other platforms. Forward slashes work everywhere.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NonPortablePath {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub path: String,
+}
+
+impl Diagnostic for NonPortablePath {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("non-portable-path")
+    }
+    fn message(&self) -> String {
+        "`#[path]` uses backslashes, which only work as path separators on Windows".to_string()
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
 // Diagnostic: unresolved-extern-crate
 //
 // This diagnostic is triggered if rust-analyzer is unable to discover referred extern crate.
@@ -64,6 +547,33 @@ impl Diagnostic for UnresolvedExternCrate {
     }
 }
 
+// Diagnostic: redundant-extern-crate-core
+//
+// This diagnostic is shown for `extern crate core;` in crates that aren't `#![no_std]`, where the
+// items of `core` are available through `std`. `extern crate alloc;` is not reported, as it is
+// required to use `alloc` even in crates that link `std`.
+#[derive(Debug)]
+pub struct RedundantExternCrateCore {
+    pub file: HirFileId,
+    pub item: AstPtr<ast::ExternCrate>,
+    pub name: String,
+}
+
+impl Diagnostic for RedundantExternCrateCore {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("redundant-extern-crate-core")
+    }
+    fn message(&self) -> String {
+        format!("`extern crate {}` is redundant, its items are available through `std`", self.name)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.item.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
 // Diagnostic: unresolved-import
 //
 // This diagnostic is triggered if rust-analyzer is unable to discover imported module.
@@ -95,6 +605,36 @@ impl Diagnostic for UnresolvedImport {
     }
 }
 
+// Diagnostic: unresolved-trait-import
+//
+// This diagnostic is triggered if rust-analyzer is unable to resolve the trait imported by an
+// underscore import, like `use foo::MissingTrait as _;`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnresolvedTraitImport {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::UseTree>,
+    pub name: String,
+}
+
+impl Diagnostic for UnresolvedTraitImport {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("unresolved-trait-import")
+    }
+    fn message(&self) -> String {
+        format!("unresolved trait `{}` in underscore import", self.name)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+    fn is_experimental(&self) -> bool {
+        // Has the same false positives as `UnresolvedImport`.
+        true
+    }
+}
+
 // Diagnostic: unresolved-macro-call
 //
 // This diagnostic is triggered if rust-analyzer is unable to resolve the path to a
@@ -124,95 +664,160 @@ impl Diagnostic for UnresolvedMacroCall {
     }
 }
 
-// Diagnostic: inactive-code
+// Diagnostic: macro-used-before-definition
 //
-// This diagnostic is shown for code with inactive `#[cfg]` attributes.
+// This diagnostic is triggered if a `macro_rules!` macro is invoked before its definition.
+// Such macros are only in scope textually, from their definition to the end of the enclosing
+// module, so an earlier invocation does not see them.
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub struct InactiveCode {
+pub struct MacroUsedBeforeDefinition {
     pub file: HirFileId,
-    pub node: SyntaxNodePtr,
-    pub cfg: CfgExpr,
-    pub opts: CfgOptions,
+    pub node: AstPtr<ast::MacroCall>,
+    pub name: String,
+    pub def: InFile<AstPtr<ast::Macro>>,
 }
 
-impl Diagnostic for InactiveCode {
+impl Diagnostic for MacroUsedBeforeDefinition {
     fn code(&self) -> DiagnosticCode {
-        DiagnosticCode("inactive-code")
+        DiagnosticCode("macro-used-before-definition")
     }
     fn message(&self) -> String {
-        let inactive = DnfExpr::new(self.cfg.clone()).why_inactive(&self.opts);
-        let mut buf = "code is inactive due to #[cfg] directives".to_string();
+        format!(
+            "macro `{}!` is used before its definition; `macro_rules!` macros are only visible \
+             after they are defined",
+            self.name
+        )
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
 
-        if let Some(inactive) = inactive {
-            format_to!(buf, ": {}", inactive);
-        }
+// Diagnostic: duplicate-macro-definition
+//
+// This diagnostic is triggered if a module defines a `macro_rules!` macro with the same name twice.
+// The second definition shadows the first one from that point on, which is rarely intended.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DuplicateMacroDefinition {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::Macro>,
+    pub name: String,
+    pub first: InFile<AstPtr<ast::Macro>>,
+}
 
-        buf
+impl Diagnostic for DuplicateMacroDefinition {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("duplicate-macro-definition")
+    }
+    fn message(&self) -> String {
+        format!("macro `{}!` is defined multiple times in this module", self.name)
     }
     fn display_source(&self) -> InFile<SyntaxNodePtr> {
-        InFile::new(self.file, self.node.clone())
+        InFile::new(self.file, self.node.clone().into())
     }
     fn as_any(&self) -> &(dyn Any + Send + 'static) {
         self
     }
 }
 
-// Diagnostic: unresolved-proc-macro
-//
-// This diagnostic is shown when a procedural macro can not be found. This usually means that
-// procedural macro support is simply disabled (and hence is only a weak hint instead of an error),
-// but can also indicate project setup problems.
+// Diagnostic: ineffective-macro-export
 //
-// If you are seeing a lot of "proc macro not expanded" warnings, you can add this option to the
-// `rust-analyzer.diagnostics.disabled` list to prevent them from showing. Alternatively you can
-// enable support for procedural macros (see `rust-analyzer.procMacro.enable`).
+// This diagnostic is triggered by `#[macro_export]` on a `macro_rules!` macro that is defined in a
+// block expression, like a function body, rather than at module level. Such macros are not
+// exported.
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub struct UnresolvedProcMacro {
+pub struct IneffectiveMacroExport {
     pub file: HirFileId,
     pub node: SyntaxNodePtr,
-    /// If the diagnostic can be pinpointed more accurately than via `node`, this is the `TextRange`
-    /// to use instead.
-    pub precise_location: Option<TextRange>,
-    pub macro_name: Option<String>,
 }
 
-impl Diagnostic for UnresolvedProcMacro {
+impl Diagnostic for IneffectiveMacroExport {
     fn code(&self) -> DiagnosticCode {
-        DiagnosticCode("unresolved-proc-macro")
+        DiagnosticCode("ineffective-macro-export")
     }
-
     fn message(&self) -> String {
-        match &self.macro_name {
-            Some(name) => format!("proc macro `{}` not expanded", name),
-            None => "proc macro not expanded".to_string(),
-        }
+        "`#[macro_export]` has no effect on macros defined inside a block".to_string()
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
     }
+}
+
+// Diagnostic: duplicate-lang-item
+//
+// This diagnostic is triggered if two items of a crate have the same `#[lang = "..."]` attribute.
+// The compiler only accepts one definition of each lang item.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DuplicateLangItem {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub lang: String,
+    pub first: InFile<AstPtr<ast::Item>>,
+}
 
+impl Diagnostic for DuplicateLangItem {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("duplicate-lang-item")
+    }
+    fn message(&self) -> String {
+        format!("duplicate lang item `{}`", self.lang)
+    }
     fn display_source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.node.clone())
     }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: redundant-visibility-on-trait-item
+//
+// This diagnostic is triggered by a visibility on an item of a trait, like `pub` in
+// `trait T { pub fn f(); }`. Trait items always have the visibility of their trait.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RedundantVisibilityOnTraitItem {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+}
 
+impl Diagnostic for RedundantVisibilityOnTraitItem {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("redundant-visibility-on-trait-item")
+    }
+    fn message(&self) -> String {
+        "visibility qualifiers are not permitted on trait items".to_string()
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
     fn as_any(&self) -> &(dyn Any + Send + 'static) {
         self
     }
 }
 
-// Diagnostic: macro-error
+// Diagnostic: disallowed-nested-impl-trait
 //
-// This diagnostic is shown for macro expansion errors.
+// This diagnostic is triggered by an `impl Trait` type in the generic arguments of another one,
+// like in `fn f() -> impl Into<impl Debug>`. Associated type bindings like
+// `impl Iterator<Item = impl Debug>` are allowed.
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub struct MacroError {
+pub struct DisallowedNestedImplTrait {
     pub file: HirFileId,
     pub node: SyntaxNodePtr,
-    pub message: String,
 }
 
-impl Diagnostic for MacroError {
+impl Diagnostic for DisallowedNestedImplTrait {
     fn code(&self) -> DiagnosticCode {
-        DiagnosticCode("macro-error")
+        DiagnosticCode("disallowed-nested-impl-trait")
     }
     fn message(&self) -> String {
-        self.message.clone()
+        "nested `impl Trait` is not allowed".to_string()
     }
     fn display_source(&self) -> InFile<SyntaxNodePtr> {
         InFile::new(self.file, self.node.clone())
@@ -220,8 +825,778 @@ impl Diagnostic for MacroError {
     fn as_any(&self) -> &(dyn Any + Send + 'static) {
         self
     }
-    fn is_experimental(&self) -> bool {
-        // Newly added and not very well-tested, might contain false positives.
-        true
+}
+
+// Diagnostic: inactive-code
+//
+// This diagnostic is shown for code with inactive `#[cfg]` attributes.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InactiveCode {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub cfg: CfgExpr,
+    pub opts: CfgOptions,
+}
+
+impl Diagnostic for InactiveCode {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("inactive-code")
+    }
+    fn message(&self) -> String {
+        let inactive = DnfExpr::new(self.cfg.clone()).why_inactive(&self.opts);
+        let mut buf = "code is inactive due to #[cfg] directives".to_string();
+
+        if let Some(inactive) = inactive {
+            format_to!(buf, ": {}", inactive);
+        }
+
+        buf
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: invalid-attribute-placement
+//
+// This diagnostic is shown for attributes on expressions other than `#[cfg]`, `#[cfg_attr]`, and
+// the lint attributes, since those are the only built-in attributes allowed there.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidAttributePlacement {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::Attr>,
+    pub attr: String,
+}
+
+impl Diagnostic for InvalidAttributePlacement {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("invalid-attribute-placement")
+    }
+    fn message(&self) -> String {
+        format!("`#[{}]` is not allowed on expressions", self.attr)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+    fn is_experimental(&self) -> bool {
+        // Nightly features add more attributes that are allowed here.
+        true
+    }
+}
+
+// Diagnostic: unresolved-proc-macro
+//
+// This diagnostic is shown when a procedural macro can not be found. This usually means that
+// procedural macro support is simply disabled (and hence is only a weak hint instead of an error),
+// but can also indicate project setup problems.
+//
+// If you are seeing a lot of "proc macro not expanded" warnings, you can add this option to the
+// `rust-analyzer.diagnostics.disabled` list to prevent them from showing. Alternatively you can
+// enable support for procedural macros (see `rust-analyzer.procMacro.enable`).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnresolvedProcMacro {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    /// If the diagnostic can be pinpointed more accurately than via `node`, this is the `TextRange`
+    /// to use instead.
+    pub precise_location: Option<TextRange>,
+    pub macro_name: Option<String>,
+}
+
+impl Diagnostic for UnresolvedProcMacro {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("unresolved-proc-macro")
+    }
+
+    fn message(&self) -> String {
+        match &self.macro_name {
+            Some(name) => format!("proc macro `{}` not expanded", name),
+            None => "proc macro not expanded".to_string(),
+        }
+    }
+
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: macro-error
+//
+// This diagnostic is shown for macro expansion errors.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MacroError {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub message: String,
+    /// Identifies the kind of error independently of `message`, for localization.
+    pub message_id: &'static str,
+    /// The values to fill into the message identified by `message_id`.
+    pub params: Vec<String>,
+}
+
+impl MacroError {
+    pub(crate) fn new(file: HirFileId, node: SyntaxNodePtr, error: &ExpandError) -> Self {
+        MacroError {
+            file,
+            node,
+            message: error.to_string(),
+            message_id: error.message_id(),
+            params: error.message_params(),
+        }
+    }
+}
+
+impl Diagnostic for MacroError {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("macro-error")
+    }
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+    fn is_experimental(&self) -> bool {
+        // Newly added and not very well-tested, might contain false positives.
+        true
+    }
+}
+
+// Diagnostic: macro-expansion-too-large
+//
+// This diagnostic is shown for macro invocations that expand to more tokens than rust-analyzer is
+// willing to process. Their expansion is ignored.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MacroExpansionTooLarge {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub token_count: usize,
+}
+
+impl Diagnostic for MacroExpansionTooLarge {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("macro-expansion-too-large")
+    }
+    fn message(&self) -> String {
+        format!("macro expansion is too large ({} tokens) and was ignored", self.token_count)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: unsupported-macro-position
+//
+// This diagnostic is shown for macro calls in positions where rust-analyzer does not expand macros
+// yet, like inside `extern` blocks. Items produced by such calls are missing from the analysis.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnsupportedMacroPosition {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::MacroCall>,
+    pub position: String,
+}
+
+impl Diagnostic for UnsupportedMacroPosition {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("unsupported-macro-position")
+    }
+    fn message(&self) -> String {
+        format!("macro calls in {} are not expanded by rust-analyzer yet", self.position)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: helper-attr-without-derive
+//
+// This diagnostic is shown for helper attributes of well-known derive macros, like `#[serde(..)]`,
+// on items that don't derive any of the macros that accept them. Such attributes are rejected by
+// rustc.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HelperAttrWithoutDerive {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub attr: String,
+}
+
+impl Diagnostic for HelperAttrWithoutDerive {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("helper-attr-without-derive")
+    }
+    fn message(&self) -> String {
+        format!("helper attribute `{}` is used without a derive that registers it", self.attr)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: overlapping-impl
+//
+// This diagnostic is triggered if a module contains several implementations of the same trait
+// for the same type with identical headers, like `impl Foo for S {}` written twice.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OverlappingImpl {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::Impl>,
+    /// The range of the header of the impl, if it could be determined.
+    pub precise_location: Option<TextRange>,
+    /// The impl that `node` conflicts with.
+    pub first: InFile<AstPtr<ast::Impl>>,
+    pub trait_: String,
+    pub self_ty: String,
+}
+
+impl Diagnostic for OverlappingImpl {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("overlapping-impl")
+    }
+    fn message(&self) -> String {
+        format!(
+            "conflicting implementations of trait `{}` for type `{}`",
+            self.trait_, self.self_ty
+        )
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: deprecated-without-note
+//
+// This diagnostic is shown for `#[deprecated]` attributes that don't explain why the item is
+// deprecated or what to use instead, like `#[deprecated]` or `#[deprecated(since = "1.0")]`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DeprecatedWithoutNote {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+}
+
+impl Diagnostic for DeprecatedWithoutNote {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("deprecated-without-note")
+    }
+    fn message(&self) -> String {
+        "deprecated item has no note explaining the deprecation".to_string()
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: empty-enum
+//
+// This diagnostic is shown for enums without any variants, like `enum Never {}`. Such enums can't
+// be constructed, which is sometimes intended but can also be an oversight.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EmptyEnum {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+}
+
+impl Diagnostic for EmptyEnum {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("empty-enum")
+    }
+    fn message(&self) -> String {
+        "enum has no variants, so it can't be constructed".to_string()
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: wrong-number-of-generic-args
+//
+// This diagnostic is shown when a type in an item signature is given more generic arguments than
+// it has type parameters, like `Vec<u8, u8>`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WrongNumberOfGenericArgs {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl Diagnostic for WrongNumberOfGenericArgs {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("wrong-number-of-generic-args")
+    }
+    fn message(&self) -> String {
+        let s = |n: usize| if n == 1 { "" } else { "s" };
+        format!(
+            "expected {} generic argument{}, found {}",
+            self.expected,
+            s(self.expected),
+            self.found
+        )
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: duplicate-exported-symbol
+//
+// This diagnostic is shown for `#[no_mangle]` or `#[export_name]` attributes that export a symbol
+// another item of the crate already exports. rustc accepts each of them on its own, but the crate
+// then fails to link.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DuplicateExportedSymbol {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub attr: String,
+}
+
+impl Diagnostic for DuplicateExportedSymbol {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("duplicate-exported-symbol")
+    }
+    fn message(&self) -> String {
+        format!("`#[{}]` exports a symbol that is already exported by another item", self.attr)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: unknown-tool-lint
+//
+// This diagnostic is shown for tool attributes like `#[unknown_tool::lint]` whose tool is neither
+// known to rustc nor registered with `#![register_tool]`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnknownToolLint {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub tool: String,
+}
+
+impl Diagnostic for UnknownToolLint {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("unknown-tool-lint")
+    }
+    fn message(&self) -> String {
+        format!("unknown tool `{}` in attribute path", self.tool)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: prelude-shadow
+//
+// This diagnostic is shown when an item declared in a module has the same name as an item from the
+// prelude, like `struct Option;`. This is legal, but often a mistake.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PreludeShadow {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub name: String,
+}
+
+impl Diagnostic for PreludeShadow {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("prelude-shadow")
+    }
+    fn message(&self) -> String {
+        format!("`{}` shadows an item from the prelude", self.name)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: glob-overrides-prelude
+//
+// This diagnostic is shown when a glob import brings in a name that is also provided by the
+// prelude. The glob import takes precedence, which might not be what the user expects.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GlobOverridesPrelude {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::UseTree>,
+    pub name: String,
+}
+
+impl Diagnostic for GlobOverridesPrelude {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("glob-overrides-prelude")
+    }
+    fn message(&self) -> String {
+        format!("glob import overrides `{}` from the prelude", self.name)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: non-ascii-identifier
+//
+// This diagnostic is shown for items whose name contains non-ASCII characters, like `fn привет()`.
+// Such names are legal, but some projects prefer to avoid them. It is off by default and can be
+// enabled with the `rust-analyzer.diagnostics.warnNonAsciiIdentifiers` setting.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NonAsciiIdentifier {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub name: String,
+}
+
+impl Diagnostic for NonAsciiIdentifier {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("non-ascii-identifier")
+    }
+    fn message(&self) -> String {
+        format!("identifier `{}` contains non-ASCII characters", self.name)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: overflowing-literal
+//
+// This diagnostic is triggered when the initializer of a `const` or `static` of a primitive integer
+// type is a literal that doesn't fit into that type, like `const X: u8 = 256;`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OverflowingLiteral {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::Expr>,
+    pub ty: String,
+}
+
+impl Diagnostic for OverflowingLiteral {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("overflowing-literal")
+    }
+    fn message(&self) -> String {
+        format!("literal out of range for `{}`", self.ty)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: bad-self-import
+//
+// This diagnostic is triggered when a `use` item ends in `self` outside of braces, like
+// `use foo::self;`. Such imports have to be written as `use foo;` or `use foo::{self};`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BadSelfImport {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::UseTree>,
+}
+
+impl Diagnostic for BadSelfImport {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("bad-self-import")
+    }
+    fn message(&self) -> String {
+        "`self` imports are only allowed within a { } list".to_string()
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: self-referential-import
+//
+// This diagnostic is triggered when a `use` item imports the name it defines itself from the same
+// module, like `use self::X;` when there is no other `X` in the module.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SelfReferentialImport {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::UseTree>,
+    pub name: String,
+}
+
+impl Diagnostic for SelfReferentialImport {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("self-referential-import")
+    }
+    fn message(&self) -> String {
+        format!("import of `{}` refers to itself", self.name)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: super-beyond-root
+//
+// This diagnostic is triggered when a `use` path has more leading `super` keywords than there are
+// modules above it, like `use super::Foo;` in the crate root.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SuperBeyondRoot {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::UseTree>,
+}
+
+impl Diagnostic for SuperBeyondRoot {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("super-beyond-root")
+    }
+    fn message(&self) -> String {
+        "there are too many leading `super` keywords".to_string()
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: glob-of-self
+//
+// This diagnostic is triggered when a glob import imports from the module it is in, like
+// `use self::*;`. It doesn't bring any new names into scope, but re-imports the module's own items.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GlobOfSelf {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::UseTree>,
+}
+
+impl Diagnostic for GlobOfSelf {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("glob-of-self")
+    }
+    fn message(&self) -> String {
+        "glob import of the module it is in".to_string()
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: crate-self-alias
+//
+// This diagnostic is triggered when `use crate as name;` gives the crate root a name that is also
+// the name of a dependency, shadowing that crate in paths like `name::Item`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CrateSelfAlias {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::UseTree>,
+    pub name: String,
+}
+
+impl Diagnostic for CrateSelfAlias {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("crate-self-alias")
+    }
+    fn message(&self) -> String {
+        format!(
+            "`crate` is imported as `{}`, which shadows the extern crate of that name",
+            self.name
+        )
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: unused-import
+//
+// This diagnostic is shown for private imports whose name is never used in the importing module or
+// its descendants. It is off by default and can be enabled with the
+// `rust-analyzer.diagnostics.warnUnusedImports` setting.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnusedImport {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::UseTree>,
+}
+
+impl Diagnostic for UnusedImport {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("unused-import")
+    }
+    fn message(&self) -> String {
+        "unused import".to_string()
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+    fn is_experimental(&self) -> bool {
+        // Usage is found by looking for the imported name, which misses imports that are shadowed.
+        true
+    }
+}
+
+// Diagnostic: reexport-of-private-module-item
+//
+// This diagnostic is shown for `pub use` items that re-export an item which isn't `pub` itself,
+// like a private or `pub(crate)` item of another module. rustc rejects these re-exports.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReExportOfPrivateModuleItem {
+    pub file: HirFileId,
+    pub node: AstPtr<ast::UseTree>,
+    pub module: String,
+}
+
+impl Diagnostic for ReExportOfPrivateModuleItem {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("reexport-of-private-module-item")
+    }
+    fn message(&self) -> String {
+        format!("`pub use` re-exports an item that isn't public in module `{}`", self.module)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone().into())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: invalid-repr
+//
+// This diagnostic is triggered by `#[repr]` attributes containing a hint rustc doesn't know about,
+// like `#[repr(banana)]`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidRepr {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    pub value: String,
+}
+
+impl Diagnostic for InvalidRepr {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("invalid-repr")
+    }
+    fn message(&self) -> String {
+        format!("unrecognized representation hint `{}`", self.value)
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: unresolved-derive-path
+//
+// This diagnostic is triggered if a qualified path in a `#[derive]` attribute, like
+// `#[derive(serde::Serialize)]`, can not be resolved.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnresolvedDerivePath {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+    /// The range of the segment that failed to resolve, if it could be found.
+    pub precise_location: Option<TextRange>,
+    pub path: ModPath,
+    pub failed_segment: usize,
+}
+
+impl Diagnostic for UnresolvedDerivePath {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("unresolved-derive-path")
+    }
+    fn message(&self) -> String {
+        match self.path.segments().get(self.failed_segment) {
+            Some(segment) => {
+                format!("failed to resolve `{}` in derive path `{}`", segment, self.path)
+            }
+            None => format!("unresolved derive path `{}`", self.path),
+        }
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+// Diagnostic: contradictory-cfg
+//
+// This diagnostic is shown for items with several `#[cfg]` attributes that can never be enabled at
+// the same time, like `#[cfg(unix)] #[cfg(windows)]`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ContradictoryCfg {
+    pub file: HirFileId,
+    pub node: SyntaxNodePtr,
+}
+
+impl Diagnostic for ContradictoryCfg {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("contradictory-cfg")
+    }
+    fn message(&self) -> String {
+        "the `cfg` attributes of this item contradict each other, so it is never enabled"
+            .to_string()
+    }
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file, self.node.clone())
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
     }
 }
@@ -54,6 +54,11 @@ pub trait Policy {
 
     fn insert(map: &mut DynMap, key: Self::K, value: Self::V);
     fn get<'a>(map: &'a DynMap, key: &Self::K) -> Option<&'a Self::V>;
+    fn values(map: &DynMap) -> Vec<&Self::V>;
+    fn len(map: &DynMap) -> usize;
+    fn is_empty(map: &DynMap) -> bool {
+        Self::len(map) == 0
+    }
 }
 
 impl<K: Hash + Eq + 'static, V: 'static> Policy for (K, V) {
@@ -65,6 +70,12 @@ impl<K: Hash + Eq + 'static, V: 'static> Policy for (K, V) {
     fn get<'a>(map: &'a DynMap, key: &K) -> Option<&'a V> {
         map.map.get::<FxHashMap<K, V>>()?.get(key)
     }
+    fn values(map: &DynMap) -> Vec<&V> {
+        map.map.get::<FxHashMap<K, V>>().map_or(Vec::new(), |it| it.values().collect())
+    }
+    fn len(map: &DynMap) -> usize {
+        map.map.get::<FxHashMap<K, V>>().map_or(0, |it| it.len())
+    }
 }
 
 pub struct DynMap {
@@ -77,9 +88,26 @@ impl Default for DynMap {
     }
 }
 
+impl DynMap {
+    /// Returns the names of the [`crate::keys`] that have at least one entry in
+    /// this map. Useful for debugging.
+    pub fn present_keys(&self) -> Vec<&'static str> {
+        crate::keys::present_keys(self)
+    }
+
+    /// The total number of entries, over all keys.
+    pub fn len(&self) -> usize {
+        crate::keys::entry_count(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 #[repr(transparent)]
 pub struct KeyMap<KEY> {
-    map: DynMap,
+    pub(crate) map: DynMap,
     _phantom: PhantomData<KEY>,
 }
 
@@ -90,6 +118,16 @@ impl<P: Policy> KeyMap<Key<P::K, P::V, P>> {
     pub fn get(&self, key: &P::K) -> Option<&P::V> {
         P::get(&self.map, key)
     }
+    /// Returns all values of this key. The order depends on the `Policy`.
+    pub fn values(&self) -> Vec<&P::V> {
+        P::values(&self.map)
+    }
+    pub fn len(&self) -> usize {
+        P::len(&self.map)
+    }
+    pub fn is_empty(&self) -> bool {
+        P::is_empty(&self.map)
+    }
 }
 
 impl<P: Policy> Index<Key<P::K, P::V, P>> for DynMap {
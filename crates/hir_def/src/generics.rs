@@ -3,6 +3,8 @@
 //! generic parameters. See also the `Generics` type and the `generics_of` query
 //! in rustc.
 
+use std::iter;
+
 use base_db::FileId;
 use either::Either;
 use hir_expand::{
@@ -10,7 +12,10 @@ use hir_expand::{
     InFile,
 };
 use la_arena::{Arena, ArenaMap};
-use syntax::ast::{self, GenericParamsOwner, NameOwner, TypeBoundsOwner};
+use syntax::{
+    ast::{self, GenericParamsOwner, NameOwner, TypeBoundsOwner},
+    AstNode, SyntaxNode, WalkEvent,
+};
 
 use crate::{
     body::LowerCtx,
@@ -19,6 +24,8 @@ use crate::{
     dyn_map::DynMap,
     intern::Interned,
     keys,
+    path::Path,
+    resolver::{HasResolver, TypeNs},
     src::{HasChildSource, HasSource},
     type_ref::{LifetimeRef, TypeBound, TypeRef},
     AdtId, ConstParamId, GenericDefId, LifetimeParamId, LocalConstParamId, LocalLifetimeParamId,
@@ -85,6 +92,7 @@ pub(crate) struct SourceMap {
     pub(crate) type_params: ArenaMap<LocalTypeParamId, Either<ast::Trait, ast::TypeParam>>,
     lifetime_params: ArenaMap<LocalLifetimeParamId, ast::LifetimeParam>,
     const_params: ArenaMap<LocalConstParamId, ast::ConstParam>,
+    where_predicates: Vec<ast::WherePred>,
 }
 
 impl GenericParams {
@@ -233,7 +241,7 @@ impl GenericParams {
             self.fill_params(lower_ctx, sm, params)
         }
         if let Some(where_clause) = node.where_clause() {
-            self.fill_where_predicates(lower_ctx, where_clause);
+            self.fill_where_predicates(lower_ctx, sm, where_clause);
         }
     }
 
@@ -290,8 +298,14 @@ impl GenericParams {
         }
     }
 
-    fn fill_where_predicates(&mut self, lower_ctx: &LowerCtx, where_clause: ast::WhereClause) {
+    fn fill_where_predicates(
+        &mut self,
+        lower_ctx: &LowerCtx,
+        sm: &mut SourceMap,
+        where_clause: ast::WhereClause,
+    ) {
         for pred in where_clause.predicates() {
+            sm.where_predicates.push(pred.clone());
             let target = if let Some(type_ref) = pred.ty() {
                 Either::Left(TypeRef::from_ast(lower_ctx, type_ref))
             } else if let Some(lifetime) = pred.lifetime() {
@@ -451,5 +465,83 @@ impl ChildBySource for GenericDefId {
             let id = ConstParamId { parent: *self, local_id };
             res[keys::CONST_PARAM].insert(sm.with_value(src.clone()), id);
         }
+        for pred in sm.value.where_predicates.iter() {
+            res[keys::WHERE_PREDICATE].insert(sm.with_value(pred.clone()), *self);
+        }
+        if let Some(src) = signature_source(db, *self) {
+            add_dyn_traits(db, res, *self, &src);
+        }
+    }
+}
+
+/// Returns the syntax of `def`, or `None` if it has no generic params of its own.
+fn signature_source(db: &dyn DefDatabase, def: GenericDefId) -> Option<InFile<SyntaxNode>> {
+    let src = match def {
+        GenericDefId::FunctionId(it) => it.lookup(db).source(db).map(|it| it.syntax().clone()),
+        GenericDefId::AdtId(AdtId::StructId(it)) => {
+            it.lookup(db).source(db).map(|it| it.syntax().clone())
+        }
+        GenericDefId::AdtId(AdtId::UnionId(it)) => {
+            it.lookup(db).source(db).map(|it| it.syntax().clone())
+        }
+        GenericDefId::AdtId(AdtId::EnumId(it)) => {
+            it.lookup(db).source(db).map(|it| it.syntax().clone())
+        }
+        GenericDefId::TraitId(it) => it.lookup(db).source(db).map(|it| it.syntax().clone()),
+        GenericDefId::TypeAliasId(it) => it.lookup(db).source(db).map(|it| it.syntax().clone()),
+        GenericDefId::ImplId(it) => it.lookup(db).source(db).map(|it| it.syntax().clone()),
+        GenericDefId::EnumVariantId(_) | GenericDefId::ConstId(_) => return None,
+    };
+    Some(src)
+}
+
+/// Preorder walk over the signature in `src`. Bodies and associated items are left out, since
+/// they are containers of their own.
+fn signature_nodes(src: &SyntaxNode) -> impl Iterator<Item = SyntaxNode> {
+    let mut preorder = src.preorder();
+    iter::from_fn(move || loop {
+        let node = match preorder.next()? {
+            WalkEvent::Enter(node) => node,
+            WalkEvent::Leave(_) => continue,
+        };
+        if ast::BlockExpr::can_cast(node.kind()) || ast::AssocItemList::can_cast(node.kind()) {
+            preorder.skip_subtree();
+            continue;
+        }
+        return Some(node);
+    })
+}
+
+/// Maps the `dyn Trait` types in the signature of `def` to their principal trait.
+fn add_dyn_traits(
+    db: &dyn DefDatabase,
+    res: &mut DynMap,
+    def: GenericDefId,
+    src: &InFile<SyntaxNode>,
+) {
+    let resolver = def.resolver(db);
+    let lower_ctx = LowerCtx::new(db, src.file_id);
+    for node in signature_nodes(&src.value) {
+        let dyn_trait = match ast::DynTraitType::cast(node) {
+            Some(it) => it,
+            None => continue,
+        };
+        let trait_ = dyn_trait
+            .type_bound_list()
+            .into_iter()
+            .flat_map(|it| it.bounds())
+            .filter_map(|bound| match bound.ty()? {
+                ast::Type::PathType(it) => Path::from_src(it.path()?, &lower_ctx),
+                _ => None,
+            })
+            .find_map(|path| {
+                match resolver.resolve_path_in_type_ns_fully(db, path.mod_path())? {
+                    TypeNs::TraitId(it) => Some(it),
+                    _ => None,
+                }
+            });
+        if let Some(trait_) = trait_ {
+            res[keys::DYN_TRAIT].insert(src.with_value(dyn_trait), trait_);
+        }
     }
 }
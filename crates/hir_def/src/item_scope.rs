@@ -5,7 +5,7 @@ use std::collections::hash_map::Entry;
 
 use base_db::CrateId;
 use hir_expand::name::Name;
-use hir_expand::MacroDefKind;
+use hir_expand::{MacroCallId, MacroDefKind};
 use once_cell::sync::Lazy;
 use rustc_hash::{FxHashMap, FxHashSet};
 use stdx::format_to;
@@ -15,6 +15,14 @@ use crate::{
     LocalModuleId, MacroDefId, ModuleDefId, ModuleId, TraitId,
 };
 
+/// The namespaces that `ModuleDefId`s live in. Macros have a namespace of their own, but aren't
+/// `ModuleDefId`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Namespace {
+    Types,
+    Values,
+}
+
 #[derive(Copy, Clone)]
 pub(crate) enum ImportType {
     Glob,
@@ -53,6 +61,8 @@ pub struct ItemScope {
     // FIXME: Macro shadowing in one module is not properly handled. Non-item place macros will
     // be all resolved to the last one defined if shadowing happens.
     legacy_macros: FxHashMap<Name, MacroDefId>,
+    /// Macro calls in this scope that were resolved, in the order they were resolved in.
+    macro_invocations: Vec<MacroCallId>,
 }
 
 pub(crate) static BUILTIN_SCOPE: Lazy<FxHashMap<Name, PerNs>> = Lazy::new(|| {
@@ -111,6 +121,10 @@ impl ItemScope {
         self.unnamed_consts.iter().copied()
     }
 
+    pub fn macro_invocations(&self) -> impl Iterator<Item = MacroCallId> + ExactSizeIterator + '_ {
+        self.macro_invocations.iter().copied()
+    }
+
     /// Iterate over all module scoped macros
     pub(crate) fn macros<'a>(&'a self) -> impl Iterator<Item = (&'a Name, MacroDefId)> + 'a {
         self.entries().filter_map(|(name, def)| def.take_macros().map(|macro_| (name, macro_)))
@@ -130,6 +144,15 @@ impl ItemScope {
         }
     }
 
+    /// Resolves `name` in the namespace `ns` of this scope.
+    pub fn resolve_name(&self, name: &Name, ns: Namespace) -> Option<ModuleDefId> {
+        let map = match ns {
+            Namespace::Types => &self.types,
+            Namespace::Values => &self.values,
+        };
+        map.get(name).map(|&(def, _)| def)
+    }
+
     pub(crate) fn name_of(&self, item: ItemInNs) -> Option<(&Name, Visibility)> {
         for (name, per_ns) in self.entries() {
             if let Some(vis) = item.match_with(per_ns) {
@@ -169,6 +192,10 @@ impl ItemScope {
         self.legacy_macros.insert(name, mac);
     }
 
+    pub(crate) fn add_macro_invocation(&mut self, call: MacroCallId) {
+        self.macro_invocations.push(call);
+    }
+
     pub(crate) fn unnamed_trait_vis(&self, tr: TraitId) -> Option<Visibility> {
         self.unnamed_trait_imports.get(&tr).copied()
     }
@@ -307,6 +334,7 @@ impl ItemScope {
             unnamed_consts,
             unnamed_trait_imports,
             legacy_macros,
+            macro_invocations,
         } = self;
         types.shrink_to_fit();
         values.shrink_to_fit();
@@ -317,6 +345,7 @@ impl ItemScope {
         unnamed_consts.shrink_to_fit();
         unnamed_trait_imports.shrink_to_fit();
         legacy_macros.shrink_to_fit();
+        macro_invocations.shrink_to_fit();
     }
 }
 
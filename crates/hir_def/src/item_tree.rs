@@ -497,6 +497,9 @@ pub struct Import {
     /// This can be used to get the `UseTree` this `Import` corresponds to and allows emitting
     /// precise diagnostics.
     pub index: usize,
+    /// Whether this import ends with a `self` segment outside of braces, like `use foo::self;`.
+    /// Such imports are lowered as if they didn't have the `self` segment.
+    pub is_bad_self: bool,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -609,6 +612,8 @@ pub struct Trait {
     pub is_unsafe: bool,
     pub bounds: Box<[TypeBound]>,
     pub items: Box<[AssocItem]>,
+    /// The items of `items` that are written with a visibility, which trait items can't have.
+    pub items_with_visibility: Box<[AssocItem]>,
     pub ast_id: FileAstId<ast::Trait>,
 }
 
@@ -656,6 +661,8 @@ pub struct MacroCall {
     /// Path to the called macro.
     pub path: Interned<ModPath>,
     pub ast_id: FileAstId<ast::MacroCall>,
+    /// Whether the call is inside an `extern` block, where macro calls are not supported yet.
+    pub in_extern_block: bool,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -773,6 +780,10 @@ impl<T> IdRange<T> {
     fn new(range: Range<Idx<T>>) -> Self {
         Self { range: range.start.into_raw().into()..range.end.into_raw().into(), _p: PhantomData }
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.range.is_empty()
+    }
 }
 
 impl<T> Iterator for IdRange<T> {
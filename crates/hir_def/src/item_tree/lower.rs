@@ -521,14 +521,20 @@ impl Ctx {
         let is_auto = trait_def.auto_token().is_some();
         let is_unsafe = trait_def.unsafe_token().is_some();
         let bounds = self.lower_type_bounds(trait_def);
+        let mut items_with_visibility = Vec::new();
         let items = trait_def.assoc_item_list().map(|list| {
             self.with_inherited_visibility(visibility, |this| {
                 list.assoc_items()
                     .filter_map(|item| {
                         let attrs = RawAttrs::new(&item, &this.hygiene);
                         this.collect_inner_items(item.syntax());
+                        let has_visibility =
+                            item.syntax().children().any(|it| ast::Visibility::can_cast(it.kind()));
                         this.lower_assoc_item(&item).map(|item| {
                             this.add_attrs(ModItem::from(item).into(), attrs);
+                            if has_visibility {
+                                items_with_visibility.push(item);
+                            }
                             item
                         })
                     })
@@ -544,6 +550,7 @@ impl Ctx {
             is_unsafe,
             bounds: bounds.into(),
             items: items.unwrap_or_default(),
+            items_with_visibility: items_with_visibility.into(),
             ast_id,
         };
         Some(id(self.data().traits.alloc(res)))
@@ -587,7 +594,13 @@ impl Ctx {
         ModPath::expand_use_item(
             InFile::new(self.file, use_item.clone()),
             &self.hygiene,
-            |path, _use_tree, is_glob, alias| {
+            |path, use_tree, is_glob, alias| {
+                let is_bad_self = use_tree.use_tree_list().is_none()
+                    && use_tree.path().map_or(false, |path| {
+                        path.qualifier().is_some()
+                            && path.segment().and_then(|it| it.kind())
+                                == Some(ast::PathSegmentKind::SelfKw)
+                    });
                 imports.push(id(tree.imports.alloc(Import {
                     path: Interned::new(path),
                     alias,
@@ -595,6 +608,7 @@ impl Ctx {
                     is_glob,
                     ast_id,
                     index: imports.len(),
+                    is_bad_self,
                 })));
             },
         );
@@ -620,7 +634,7 @@ impl Ctx {
     fn lower_macro_call(&mut self, m: &ast::MacroCall) -> Option<FileItemTreeId<MacroCall>> {
         let path = Interned::new(ModPath::from_src(m.path()?, &self.hygiene)?);
         let ast_id = self.source_ast_id_map.ast_id(m);
-        let res = MacroCall { path, ast_id };
+        let res = MacroCall { path, ast_id, in_extern_block: false };
         Some(id(self.data().macro_calls.alloc(res)))
     }
 
@@ -668,7 +682,11 @@ impl Ctx {
                             self.data().type_aliases[foreign_ty.index].is_extern = true;
                             foreign_ty.into()
                         }
-                        ast::ExternItem::MacroCall(_) => return None,
+                        ast::ExternItem::MacroCall(ast) => {
+                            let call = self.lower_macro_call(&ast)?;
+                            self.data().macro_calls[call.index].in_extern_block = true;
+                            call.into()
+                        }
                     };
                     self.add_attrs(id.into(), attrs);
                     Some(id)
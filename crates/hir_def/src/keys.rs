@@ -1,15 +1,16 @@
 //! keys to be used with `DynMap`
 
-use std::marker::PhantomData;
+use std::{hash::BuildHasherDefault, marker::PhantomData};
 
 use hir_expand::{InFile, MacroDefId};
-use rustc_hash::FxHashMap;
+use indexmap::IndexMap;
+use rustc_hash::FxHasher;
 use syntax::{ast, AstNode, AstPtr};
 
 use crate::{
-    dyn_map::{DynMap, Policy},
-    ConstId, ConstParamId, EnumId, EnumVariantId, FieldId, FunctionId, ImplId, LifetimeParamId,
-    StaticId, StructId, TraitId, TypeAliasId, TypeParamId, UnionId,
+    dyn_map::{DynMap, KeyMap, Policy},
+    ConstId, ConstParamId, EnumId, EnumVariantId, FieldId, FunctionId, GenericDefId, ImplId,
+    LifetimeParamId, ModuleId, StaticId, StructId, TraitId, TypeAliasId, TypeParamId, UnionId,
 };
 
 pub type Key<K, V> = crate::dyn_map::Key<InFile<K>, V, AstPtrPolicy<K, V>>;
@@ -23,6 +24,7 @@ pub const TRAIT: Key<ast::Trait, TraitId> = Key::new();
 pub const STRUCT: Key<ast::Struct, StructId> = Key::new();
 pub const UNION: Key<ast::Union, UnionId> = Key::new();
 pub const ENUM: Key<ast::Enum, EnumId> = Key::new();
+pub const MODULE: Key<ast::Module, ModuleId> = Key::new();
 
 pub const VARIANT: Key<ast::Variant, EnumVariantId> = Key::new();
 pub const TUPLE_FIELD: Key<ast::TupleField, FieldId> = Key::new();
@@ -30,15 +32,85 @@ pub const RECORD_FIELD: Key<ast::RecordField, FieldId> = Key::new();
 pub const TYPE_PARAM: Key<ast::TypeParam, TypeParamId> = Key::new();
 pub const LIFETIME_PARAM: Key<ast::LifetimeParam, LifetimeParamId> = Key::new();
 pub const CONST_PARAM: Key<ast::ConstParam, ConstParamId> = Key::new();
+pub const WHERE_PREDICATE: Key<ast::WherePred, GenericDefId> = Key::new();
+
+pub const DYN_TRAIT: Key<ast::DynTraitType, TraitId> = Key::new();
 
 pub const MACRO: Key<ast::MacroCall, MacroDefId> = Key::new();
 
+/// Invokes `$callback!` with the names of all keys.
+macro_rules! with_all_keys {
+    ($callback:ident) => {
+        $callback![
+            FUNCTION,
+            CONST,
+            STATIC,
+            TYPE_ALIAS,
+            IMPL,
+            TRAIT,
+            STRUCT,
+            UNION,
+            ENUM,
+            MODULE,
+            VARIANT,
+            TUPLE_FIELD,
+            RECORD_FIELD,
+            TYPE_PARAM,
+            LIFETIME_PARAM,
+            CONST_PARAM,
+            WHERE_PREDICATE,
+            DYN_TRAIT,
+            MACRO,
+        ]
+    };
+}
+
+pub(crate) fn present_keys(map: &DynMap) -> Vec<&'static str> {
+    macro_rules! present {
+        ($($key:ident),* $(,)?) => {{
+            let mut res = Vec::new();
+            $(
+                if !map[$key].is_empty() {
+                    res.push(stringify!($key));
+                }
+            )*
+            res
+        }};
+    }
+    with_all_keys!(present)
+}
+
+pub(crate) fn entry_count(map: &DynMap) -> usize {
+    macro_rules! count {
+        ($($key:ident),* $(,)?) => {
+            0 $(+ map[$key].len())*
+        };
+    }
+    with_all_keys!(count)
+}
+
+impl<AST: AstNode + 'static, ID: 'static> KeyMap<Key<AST, ID>> {
+    /// Returns the pointers to the nodes of this key together with their values, in the order they
+    /// were inserted in.
+    pub fn ptrs(&self) -> Vec<(InFile<AstPtr<AST>>, &ID)> {
+        self.map
+            .map
+            .get::<FxIndexMap<InFile<AstPtr<AST>>, ID>>()
+            .map_or(Vec::new(), |it| it.iter().map(|(ptr, id)| (ptr.clone(), id)).collect())
+    }
+}
+
+type FxIndexMap<K, V> = IndexMap<K, V, BuildHasherDefault<FxHasher>>;
+
 /// XXX: AST Nodes and SyntaxNodes have identity equality semantics: nodes are
 /// equal if they point to exactly the same object.
 ///
 /// In general, we do not guarantee that we have exactly one instance of a
 /// syntax tree for each file. We probably should add such guarantee, but, for
 /// the time being, we will use identity-less AstPtr comparison.
+///
+/// Entries are kept in insertion order, so that the contents of a `DynMap` built
+/// by `child_by_source` are reproducible.
 pub struct AstPtrPolicy<AST, ID> {
     _phantom: PhantomData<(AST, ID)>,
 }
@@ -49,12 +121,20 @@ impl<AST: AstNode + 'static, ID: 'static> Policy for AstPtrPolicy<AST, ID> {
     fn insert(map: &mut DynMap, key: InFile<AST>, value: ID) {
         let key = key.as_ref().map(AstPtr::new);
         map.map
-            .entry::<FxHashMap<InFile<AstPtr<AST>>, ID>>()
+            .entry::<FxIndexMap<InFile<AstPtr<AST>>, ID>>()
             .or_insert_with(Default::default)
             .insert(key, value);
     }
     fn get<'a>(map: &'a DynMap, key: &InFile<AST>) -> Option<&'a ID> {
         let key = key.as_ref().map(AstPtr::new);
-        map.map.get::<FxHashMap<InFile<AstPtr<AST>>, ID>>()?.get(&key)
+        map.map.get::<FxIndexMap<InFile<AstPtr<AST>>, ID>>()?.get(&key)
+    }
+    fn values(map: &DynMap) -> Vec<&ID> {
+        map.map
+            .get::<FxIndexMap<InFile<AstPtr<AST>>, ID>>()
+            .map_or(Vec::new(), |it| it.values().collect())
+    }
+    fn len(map: &DynMap) -> usize {
+        map.map.get::<FxIndexMap<InFile<AstPtr<AST>>, ID>>().map_or(0, |it| it.len())
     }
 }
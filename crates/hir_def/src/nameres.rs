@@ -58,24 +58,31 @@ mod proc_macro;
 use std::sync::Arc;
 
 use base_db::{CrateId, Edition, FileId};
-use hir_expand::{diagnostics::DiagnosticSink, name::Name, InFile, MacroDefId};
+use hir_expand::{diagnostics::DiagnosticSink, name::Name, InFile, MacroCallKind, MacroDefId};
 use la_arena::Arena;
 use profile::Count;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use stdx::format_to;
 use syntax::ast;
 
 use crate::{
     db::DefDatabase,
     item_scope::{BuiltinShadowMode, ItemScope},
-    nameres::{diagnostics::DefDiagnostic, path_resolution::ResolveMode},
+    nameres::path_resolution::ResolveMode,
     path::ModPath,
     per_ns::PerNs,
+    visibility::Visibility,
     AstId, BlockId, BlockLoc, LocalModuleId, ModuleDefId, ModuleId,
 };
 
 use self::proc_macro::ProcMacroDef;
 
+pub(crate) use self::diagnostics::diagnostics_affecting_file_query;
+pub use self::diagnostics::{
+    group_by_module, summarize, AnyDiagnostic, DedupDiagnostics, DefDiagnostic, DiagnosticCategory,
+    DiagnosticsDelta, Severity,
+};
+
 /// Contains the results of (early) name resolution.
 ///
 /// A `DefMap` stores the module tree and the definitions that are in scope in every module after
@@ -205,6 +212,16 @@ pub struct ModuleData {
     pub origin: ModuleOrigin,
 }
 
+/// Totals over the macro calls that name resolution expanded in a crate, to help spot macros whose
+/// expansions are unexpectedly large.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MacroExpansionStats {
+    /// The number of expanded macro calls, including the ones in other expansions.
+    pub count: usize,
+    /// The number of tokens in all expansions together.
+    pub total_tokens: usize,
+}
+
 impl DefMap {
     pub(crate) fn crate_def_map_query(db: &dyn DefDatabase, krate: CrateId) -> Arc<DefMap> {
         let _p = profile::span("crate_def_map_query").detail(|| {
@@ -216,6 +233,122 @@ impl DefMap {
         Arc::new(def_map)
     }
 
+    /// Runs name resolution for `krate` and passes each diagnostic to `on_diagnostic` as soon as
+    /// it's found, instead of collecting all of them in a `DefMap` first.
+    ///
+    /// Unlike `crate_def_map`, this isn't memoized, so it's meant for crates that produce so many
+    /// diagnostics that holding on to them is a problem.
+    pub fn collect_diagnostics(
+        db: &dyn DefDatabase,
+        krate: CrateId,
+        on_diagnostic: &mut dyn FnMut(DefDiagnostic),
+    ) {
+        DefMap::collect_diagnostics_with_min_severity(
+            db,
+            krate,
+            Severity::WeakWarning,
+            on_diagnostic,
+        )
+    }
+
+    /// Like `collect_diagnostics`, but only passes on diagnostics that are at least as severe as
+    /// `min_severity`. Checks that can only find less severe diagnostics are skipped entirely.
+    pub fn collect_diagnostics_with_min_severity(
+        db: &dyn DefDatabase,
+        krate: CrateId,
+        min_severity: Severity,
+        on_diagnostic: &mut dyn FnMut(DefDiagnostic),
+    ) {
+        let _p = profile::span("collect_diagnostics");
+        let edition = db.crate_graph()[krate].edition;
+        let def_map = DefMap::empty(krate, edition);
+        collector::collect_defs_streaming(db, def_map, min_severity, on_diagnostic);
+    }
+
+    pub(crate) fn module_children_query(db: &dyn DefDatabase, module: ModuleId) -> Arc<[ModuleId]> {
+        let def_map = module.def_map(db);
+        let mut children: Vec<_> = def_map[module.local_id].children.values().copied().collect();
+        // Modules are allocated as they are collected, so this is the order of their declarations.
+        children.sort_by_key(|it| it.into_raw());
+        children.into_iter().map(|it| def_map.module_id(it)).collect()
+    }
+
+    pub(crate) fn all_macro_calls_query(
+        db: &dyn DefDatabase,
+        module: ModuleId,
+    ) -> Arc<[(MacroCallKind, MacroDefId)]> {
+        let def_map = module.def_map(db);
+        def_map[module.local_id]
+            .scope
+            .macro_invocations()
+            .map(|call| call.call_and_def(db.upcast()))
+            .collect()
+    }
+
+    pub(crate) fn macro_expansion_stats_query(
+        db: &dyn DefDatabase,
+        krate: CrateId,
+    ) -> MacroExpansionStats {
+        let def_map = db.crate_def_map(krate);
+        let mut stats = MacroExpansionStats::default();
+        for (_, module) in def_map.modules() {
+            for call in module.scope.macro_invocations() {
+                if let Some(expansion) = db.macro_expand(call).value {
+                    stats.count += 1;
+                    stats.total_tokens += expansion.count();
+                }
+            }
+        }
+        stats
+    }
+
+    pub(crate) fn reexports_query(
+        db: &dyn DefDatabase,
+        module: ModuleId,
+    ) -> Arc<[(Name, ModuleDefId)]> {
+        let def_map = module.def_map(db);
+        let scope = &def_map[module.local_id].scope;
+        let declared: FxHashSet<_> = scope.declarations().collect();
+        let mut reexports: Vec<_> = scope
+            .entries()
+            .flat_map(|(name, def)| {
+                def.types.into_iter().chain(def.values).map(move |it| (name.clone(), it))
+            })
+            // An item is only re-exported if it is visible outside of `module`.
+            .filter(|(_, (_, vis))| *vis != Visibility::Module(module))
+            .filter(|(_, (def, _))| !declared.contains(def))
+            .map(|(name, (def, _))| (name, def))
+            .collect();
+        reexports.sort_by(|(a, _), (b, _)| a.cmp(b));
+        reexports.dedup();
+        reexports.into()
+    }
+
+    pub(crate) fn canonical_paths_query(
+        db: &dyn DefDatabase,
+        krate: CrateId,
+    ) -> Arc<FxHashMap<String, ModuleDefId>> {
+        let def_map = db.crate_def_map(krate);
+        let mut res = FxHashMap::default();
+        let mut modules = vec![(def_map.root(), "crate".to_string())];
+        while let Some((module, prefix)) = modules.pop() {
+            let scope = &def_map[module].scope;
+            let declared: FxHashSet<_> = scope.declarations().collect();
+            for (name, def) in scope.entries() {
+                // Values go first, so that a type of the same name takes precedence.
+                for def in def.take_values().into_iter().chain(def.take_types()) {
+                    if declared.contains(&def) {
+                        res.insert(format!("{}::{}", prefix, name), def);
+                    }
+                }
+            }
+            for (name, &child) in def_map[module].children.iter() {
+                modules.push((child, format!("{}::{}", prefix, name)));
+            }
+        }
+        Arc::new(res)
+    }
+
     pub(crate) fn block_def_map_query(
         db: &dyn DefDatabase,
         block_id: BlockId,
@@ -263,6 +396,29 @@ impl DefMap {
         self.diagnostics.iter().for_each(|it| it.add_to(db, module, sink))
     }
 
+    /// Reports the diagnostics of a block `DefMap` that only apply to items in blocks, like a
+    /// `#[macro_export]` that has no effect.
+    pub(crate) fn add_block_diagnostics(&self, db: &dyn DefDatabase, sink: &mut DiagnosticSink) {
+        self.diagnostics
+            .iter()
+            .filter(|it| it.is_block_only())
+            .for_each(|it| it.add_to_sink(db, sink))
+    }
+
+    pub fn diagnostics(&self) -> &[DefDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Compares the diagnostics of this `DefMap` to `previous`, the `diagnostics()` of the same
+    /// `DefMap` in an earlier revision, so that only the changes need to be reported after an
+    /// edit.
+    ///
+    /// Diagnostics refer to items by ids that are stable across most edits, but an edit that adds
+    /// or removes items before an item can make its diagnostics show up as removed and re-added.
+    pub fn diagnostics_since(&self, previous: &[DefDiagnostic]) -> DiagnosticsDelta {
+        DiagnosticsDelta::between(previous, &self.diagnostics)
+    }
+
     pub fn modules_for_file(&self, file_id: FileId) -> impl Iterator<Item = LocalModuleId> + '_ {
         self.modules
             .iter()
@@ -473,40 +629,629 @@ pub enum ModuleSource {
 }
 
 mod diagnostics {
+    use std::{
+        hash::{Hash, Hasher},
+        iter,
+        sync::Arc,
+    };
+
+    use base_db::{CrateId, FileId};
     use cfg::{CfgExpr, CfgOptions};
-    use hir_expand::diagnostics::DiagnosticSink;
+    use hir_expand::diagnostics::{DiagnosticCode, DiagnosticSink, DiagnosticSinkBuilder};
     use hir_expand::hygiene::Hygiene;
-    use hir_expand::{InFile, MacroCallKind};
-    use syntax::ast::AttrsOwner;
-    use syntax::{ast, AstNode, AstPtr, SyntaxKind, SyntaxNodePtr};
+    use hir_expand::{ExpandError, HirFileId, InFile, MacroCallKind};
+    use rustc_hash::{FxHashMap, FxHashSet};
+    use syntax::ast::{AttrsOwner, NameOwner};
+    use syntax::{ast, AstNode, AstPtr, SyntaxKind, SyntaxNodePtr, SyntaxToken, TextRange};
 
+    use crate::item_tree::{self, ItemTreeId};
     use crate::path::ModPath;
-    use crate::{db::DefDatabase, diagnostics::*, nameres::LocalModuleId, AstId};
+    use crate::{db::DefDatabase, diagnostics::*, nameres::LocalModuleId, AstId, ModuleId};
 
-    #[derive(Debug, PartialEq, Eq)]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
     enum DiagnosticKind {
-        UnresolvedModule { declaration: AstId<ast::Module>, candidate: String },
+        UnresolvedModule {
+            declaration: AstId<ast::Module>,
+            candidate: String,
+        },
+
+        ModuleInlineAndFile {
+            ast: AstId<ast::Module>,
+            file: String,
+        },
+
+        NonPortablePath {
+            ast: AstId<ast::Module>,
+            path: String,
+        },
+
+        UnresolvedExternCrate {
+            ast: AstId<ast::ExternCrate>,
+        },
+
+        RedundantExternCrateCore {
+            ast: AstId<ast::ExternCrate>,
+            name: String,
+        },
+
+        UnresolvedImport {
+            ast: AstId<ast::Use>,
+            index: usize,
+        },
+
+        UnconfiguredCode {
+            ast: AstId<ast::Item>,
+            cfg: CfgExpr,
+            opts: Unhashed<CfgOptions>,
+        },
+
+        UnresolvedProcMacro {
+            ast: MacroCallKind,
+        },
+
+        UnresolvedMacroCall {
+            ast: AstId<ast::MacroCall>,
+            path: ModPath,
+        },
+
+        MacroError {
+            ast: MacroCallKind,
+            error: Unhashed<ExpandError>,
+        },
+
+        MacroUsedBeforeDefinition {
+            call: AstId<ast::MacroCall>,
+            def: AstId<ast::Macro>,
+        },
+
+        UnsupportedMacroPosition {
+            ast: AstId<ast::MacroCall>,
+            position: String,
+        },
+
+        DuplicateMacroDefinition {
+            first: AstId<ast::Macro>,
+            second: AstId<ast::Macro>,
+            name: String,
+        },
+
+        HelperAttrWithoutDerive {
+            ast: AstId<ast::Item>,
+            attr: String,
+        },
+
+        IneffectiveMacroExport {
+            ast: AstId<ast::Macro>,
+        },
+
+        PreludeShadow {
+            ast: AstId<ast::Item>,
+            name: String,
+        },
+
+        GlobOverridesPrelude {
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+            name: String,
+        },
+
+        NonAsciiIdentifier {
+            ast: AstId<ast::Item>,
+            name: String,
+        },
+
+        BadSelfImport {
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        },
+
+        SelfReferentialImport {
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        },
+
+        SuperBeyondRoot {
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        },
+
+        CrateSelfAlias {
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+            name: String,
+        },
+
+        GlobOfSelf {
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        },
+
+        InvalidRepr {
+            ast: AstId<ast::Item>,
+            value: String,
+        },
+
+        UnresolvedDerivePath {
+            ast: AstId<ast::Item>,
+            path: ModPath,
+            failed_segment: usize,
+        },
+
+        ContradictoryCfg {
+            ast: AstId<ast::Item>,
+        },
+
+        UnresolvedTraitImport {
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        },
+
+        MacroExpansionTooLarge {
+            ast: MacroCallKind,
+            token_count: usize,
+        },
+
+        OverlappingImpl {
+            first: AstId<ast::Impl>,
+            second: AstId<ast::Impl>,
+        },
+
+        DuplicateLangItem {
+            first: AstId<ast::Item>,
+            second: AstId<ast::Item>,
+            lang: String,
+        },
+
+        DeprecatedWithoutNote {
+            ast: AstId<ast::Item>,
+        },
+
+        EmptyEnum {
+            ast: AstId<ast::Enum>,
+        },
+
+        UnknownToolLint {
+            ast: AstId<ast::Item>,
+            tool: String,
+        },
+
+        DuplicateExportedSymbol {
+            ast: AstId<ast::Item>,
+            attr: String,
+        },
+
+        OverflowingLiteral {
+            ast: AstId<ast::Item>,
+            ty: String,
+        },
 
-        UnresolvedExternCrate { ast: AstId<ast::ExternCrate> },
+        WrongNumberOfGenericArgs {
+            ast: AstId<ast::Item>,
+            path: ModPath,
+            expected: usize,
+            found: usize,
+        },
+
+        RedundantVisibilityOnTraitItem {
+            ast: AstId<ast::Item>,
+        },
+
+        DisallowedNestedImplTrait {
+            ast: AstId<ast::Fn>,
+        },
+
+        ReExportOfPrivateModuleItem {
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+            module: ModuleId,
+        },
+
+        UnusedImport {
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        },
+    }
+
+    /// How severe a diagnostic is, matching how the IDE layer presents it.
+    ///
+    /// Variants are ordered from least to most severe.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum Severity {
+        /// Legal code that is often unintended, like a declaration shadowing the prelude.
+        WeakWarning,
+        Warning,
+        Error,
+    }
+
+    /// What a diagnostic is about, for grouping diagnostics in the UI.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum DiagnosticCategory {
+        Import,
+        Macro,
+        Cfg,
+        Module,
+        Visibility,
+        Other,
+    }
 
-        UnresolvedImport { ast: AstId<ast::Use>, index: usize },
+    impl DiagnosticKind {
+        fn category(&self) -> DiagnosticCategory {
+            match self {
+                DiagnosticKind::UnresolvedExternCrate { .. }
+                | DiagnosticKind::UnresolvedImport { .. }
+                | DiagnosticKind::GlobOverridesPrelude { .. }
+                | DiagnosticKind::UnresolvedTraitImport { .. }
+                | DiagnosticKind::BadSelfImport { .. }
+                | DiagnosticKind::SelfReferentialImport { .. }
+                | DiagnosticKind::SuperBeyondRoot { .. }
+                | DiagnosticKind::CrateSelfAlias { .. }
+                | DiagnosticKind::GlobOfSelf { .. }
+                | DiagnosticKind::RedundantExternCrateCore { .. }
+                | DiagnosticKind::UnusedImport { .. } => DiagnosticCategory::Import,
+                DiagnosticKind::UnresolvedProcMacro { .. }
+                | DiagnosticKind::UnresolvedMacroCall { .. }
+                | DiagnosticKind::MacroError { .. }
+                | DiagnosticKind::MacroUsedBeforeDefinition { .. }
+                | DiagnosticKind::UnresolvedDerivePath { .. }
+                | DiagnosticKind::MacroExpansionTooLarge { .. }
+                | DiagnosticKind::UnsupportedMacroPosition { .. }
+                | DiagnosticKind::HelperAttrWithoutDerive { .. }
+                | DiagnosticKind::DuplicateMacroDefinition { .. }
+                | DiagnosticKind::IneffectiveMacroExport { .. } => DiagnosticCategory::Macro,
+                DiagnosticKind::UnconfiguredCode { .. }
+                | DiagnosticKind::ContradictoryCfg { .. } => DiagnosticCategory::Cfg,
+                DiagnosticKind::UnresolvedModule { .. }
+                | DiagnosticKind::ModuleInlineAndFile { .. }
+                | DiagnosticKind::NonPortablePath { .. } => DiagnosticCategory::Module,
+                DiagnosticKind::ReExportOfPrivateModuleItem { .. }
+                | DiagnosticKind::RedundantVisibilityOnTraitItem { .. } => {
+                    DiagnosticCategory::Visibility
+                }
+                DiagnosticKind::PreludeShadow { .. }
+                | DiagnosticKind::InvalidRepr { .. }
+                | DiagnosticKind::OverlappingImpl { .. }
+                | DiagnosticKind::DuplicateLangItem { .. }
+                | DiagnosticKind::WrongNumberOfGenericArgs { .. }
+                | DiagnosticKind::DisallowedNestedImplTrait { .. }
+                | DiagnosticKind::NonAsciiIdentifier { .. }
+                | DiagnosticKind::DeprecatedWithoutNote { .. }
+                | DiagnosticKind::EmptyEnum { .. }
+                | DiagnosticKind::UnknownToolLint { .. }
+                | DiagnosticKind::DuplicateExportedSymbol { .. }
+                | DiagnosticKind::OverflowingLiteral { .. } => DiagnosticCategory::Other,
+            }
+        }
 
-        UnconfiguredCode { ast: AstId<ast::Item>, cfg: CfgExpr, opts: CfgOptions },
+        fn severity(&self) -> Severity {
+            match self {
+                DiagnosticKind::UnresolvedModule { .. }
+                | DiagnosticKind::UnresolvedExternCrate { .. }
+                | DiagnosticKind::UnresolvedImport { .. }
+                | DiagnosticKind::UnresolvedMacroCall { .. }
+                | DiagnosticKind::MacroError { .. }
+                | DiagnosticKind::MacroUsedBeforeDefinition { .. }
+                | DiagnosticKind::InvalidRepr { .. }
+                | DiagnosticKind::UnresolvedDerivePath { .. }
+                | DiagnosticKind::UnresolvedTraitImport { .. }
+                | DiagnosticKind::OverlappingImpl { .. }
+                | DiagnosticKind::DuplicateLangItem { .. }
+                | DiagnosticKind::RedundantVisibilityOnTraitItem { .. }
+                | DiagnosticKind::WrongNumberOfGenericArgs { .. }
+                | DiagnosticKind::OverflowingLiteral { .. }
+                | DiagnosticKind::DisallowedNestedImplTrait { .. }
+                | DiagnosticKind::BadSelfImport { .. }
+                | DiagnosticKind::SelfReferentialImport { .. }
+                | DiagnosticKind::SuperBeyondRoot { .. } => Severity::Error,
+                DiagnosticKind::UnconfiguredCode { .. }
+                | DiagnosticKind::UnresolvedProcMacro { .. }
+                | DiagnosticKind::CrateSelfAlias { .. }
+                | DiagnosticKind::GlobOfSelf { .. }
+                | DiagnosticKind::NonAsciiIdentifier { .. }
+                | DiagnosticKind::ReExportOfPrivateModuleItem { .. }
+                | DiagnosticKind::UnusedImport { .. }
+                | DiagnosticKind::MacroExpansionTooLarge { .. }
+                | DiagnosticKind::UnsupportedMacroPosition { .. }
+                | DiagnosticKind::HelperAttrWithoutDerive { .. }
+                | DiagnosticKind::ModuleInlineAndFile { .. }
+                | DiagnosticKind::NonPortablePath { .. }
+                | DiagnosticKind::DuplicateMacroDefinition { .. }
+                | DiagnosticKind::IneffectiveMacroExport { .. }
+                | DiagnosticKind::DuplicateExportedSymbol { .. }
+                | DiagnosticKind::UnknownToolLint { .. } => Severity::Warning,
+                DiagnosticKind::PreludeShadow { .. }
+                | DiagnosticKind::GlobOverridesPrelude { .. }
+                | DiagnosticKind::RedundantExternCrateCore { .. }
+                | DiagnosticKind::ContradictoryCfg { .. }
+                | DiagnosticKind::DeprecatedWithoutNote { .. }
+                | DiagnosticKind::EmptyEnum { .. } => Severity::WeakWarning,
+            }
+        }
 
-        UnresolvedProcMacro { ast: MacroCallKind },
+        /// A stable code identifying this kind of diagnostic, the same one that the diagnostic
+        /// reports once it's converted into its syntax-based form.
+        fn code(&self) -> &'static str {
+            match self {
+                DiagnosticKind::UnresolvedModule { .. } => "unresolved-module",
+                DiagnosticKind::ModuleInlineAndFile { .. } => "module-inline-and-file",
+                DiagnosticKind::NonPortablePath { .. } => "non-portable-path",
+                DiagnosticKind::UnresolvedExternCrate { .. } => "unresolved-extern-crate",
+                DiagnosticKind::RedundantExternCrateCore { .. } => "redundant-extern-crate-core",
+                DiagnosticKind::UnresolvedImport { .. } => "unresolved-import",
+                DiagnosticKind::UnconfiguredCode { .. } => "inactive-code",
+                DiagnosticKind::UnresolvedProcMacro { .. } => "unresolved-proc-macro",
+                DiagnosticKind::UnresolvedMacroCall { .. } => "unresolved-macro-call",
+                DiagnosticKind::MacroError { .. } => "macro-error",
+                DiagnosticKind::MacroUsedBeforeDefinition { .. } => "macro-used-before-definition",
+                DiagnosticKind::UnsupportedMacroPosition { .. } => "unsupported-macro-position",
+                DiagnosticKind::DuplicateMacroDefinition { .. } => "duplicate-macro-definition",
+                DiagnosticKind::HelperAttrWithoutDerive { .. } => "helper-attr-without-derive",
+                DiagnosticKind::IneffectiveMacroExport { .. } => "ineffective-macro-export",
+                DiagnosticKind::PreludeShadow { .. } => "prelude-shadow",
+                DiagnosticKind::GlobOverridesPrelude { .. } => "glob-overrides-prelude",
+                DiagnosticKind::NonAsciiIdentifier { .. } => "non-ascii-identifier",
+                DiagnosticKind::BadSelfImport { .. } => "bad-self-import",
+                DiagnosticKind::SelfReferentialImport { .. } => "self-referential-import",
+                DiagnosticKind::SuperBeyondRoot { .. } => "super-beyond-root",
+                DiagnosticKind::CrateSelfAlias { .. } => "crate-self-alias",
+                DiagnosticKind::GlobOfSelf { .. } => "glob-of-self",
+                DiagnosticKind::InvalidRepr { .. } => "invalid-repr",
+                DiagnosticKind::UnresolvedDerivePath { .. } => "unresolved-derive-path",
+                DiagnosticKind::ContradictoryCfg { .. } => "contradictory-cfg",
+                DiagnosticKind::UnresolvedTraitImport { .. } => "unresolved-trait-import",
+                DiagnosticKind::MacroExpansionTooLarge { .. } => "macro-expansion-too-large",
+                DiagnosticKind::OverlappingImpl { .. } => "overlapping-impl",
+                DiagnosticKind::DuplicateLangItem { .. } => "duplicate-lang-item",
+                DiagnosticKind::RedundantVisibilityOnTraitItem { .. } => {
+                    "redundant-visibility-on-trait-item"
+                }
+                DiagnosticKind::DisallowedNestedImplTrait { .. } => "disallowed-nested-impl-trait",
+                DiagnosticKind::DeprecatedWithoutNote { .. } => "deprecated-without-note",
+                DiagnosticKind::EmptyEnum { .. } => "empty-enum",
+                DiagnosticKind::UnknownToolLint { .. } => "unknown-tool-lint",
+                DiagnosticKind::DuplicateExportedSymbol { .. } => "duplicate-exported-symbol",
+                DiagnosticKind::WrongNumberOfGenericArgs { .. } => "wrong-number-of-generic-args",
+                DiagnosticKind::OverflowingLiteral { .. } => "overflowing-literal",
+                DiagnosticKind::ReExportOfPrivateModuleItem { .. } => {
+                    "reexport-of-private-module-item"
+                }
+                DiagnosticKind::UnusedImport { .. } => "unused-import",
+            }
+        }
+    }
 
-        UnresolvedMacroCall { ast: AstId<ast::MacroCall>, path: ModPath },
+    /// Diagnostic data that isn't `Hash`, like `CfgOptions`. It's left out of the hash but still
+    /// compared by `Eq`.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Unhashed<T>(T);
 
-        MacroError { ast: MacroCallKind, message: String },
+    impl<T> Hash for Unhashed<T> {
+        fn hash<H: Hasher>(&self, _state: &mut H) {}
     }
 
-    #[derive(Debug, PartialEq, Eq)]
-    pub(super) struct DefDiagnostic {
+    /// A diagnostic found during name resolution, identified by hir ids rather than by syntax.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct DefDiagnostic {
         in_module: LocalModuleId,
         kind: DiagnosticKind,
     }
 
+    /// Collects diagnostics, dropping any that are equal to one collected before.
+    ///
+    /// Diagnostics keep the order in which they were first pushed.
+    #[derive(Debug, Default)]
+    pub struct DedupDiagnostics {
+        seen: FxHashSet<DefDiagnostic>,
+        diagnostics: Vec<DefDiagnostic>,
+    }
+
+    impl DedupDiagnostics {
+        /// Adds `diag` unless an equal diagnostic was already pushed. Returns whether it was added.
+        pub fn push(&mut self, diag: DefDiagnostic) -> bool {
+            if !self.seen.insert(diag.clone()) {
+                return false;
+            }
+            self.diagnostics.push(diag);
+            true
+        }
+
+        pub fn len(&self) -> usize {
+            self.diagnostics.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.diagnostics.is_empty()
+        }
+
+        pub fn into_vec(self) -> Vec<DefDiagnostic> {
+            self.diagnostics
+        }
+    }
+
+    /// The diagnostics that appeared and disappeared between two snapshots of the same diagnostics.
+    #[derive(Debug, Default, Clone, PartialEq, Eq)]
+    pub struct DiagnosticsDelta {
+        pub added: Vec<DefDiagnostic>,
+        pub removed: Vec<DefDiagnostic>,
+    }
+
+    impl DiagnosticsDelta {
+        /// Computes the changes from `previous` to `current`, keeping the order of both.
+        pub fn between(previous: &[DefDiagnostic], current: &[DefDiagnostic]) -> Self {
+            let previous_set: FxHashSet<_> = previous.iter().collect();
+            let current_set: FxHashSet<_> = current.iter().collect();
+            DiagnosticsDelta {
+                added: current.iter().filter(|it| !previous_set.contains(it)).cloned().collect(),
+                removed: previous.iter().filter(|it| !current_set.contains(it)).cloned().collect(),
+            }
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.added.is_empty() && self.removed.is_empty()
+        }
+    }
+
+    /// A diagnostic with everything a consumer needs to show it, independent of its kind.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct AnyDiagnostic {
+        pub code: &'static str,
+        pub message: String,
+        pub severity: Severity,
+        /// The file `range` is in, which may be a macro file.
+        pub file: HirFileId,
+        pub range: TextRange,
+    }
+
+    /// Groups `diags` by the module they were emitted in, preserving their relative order.
+    pub fn group_by_module(
+        diags: Vec<DefDiagnostic>,
+    ) -> FxHashMap<LocalModuleId, Vec<DefDiagnostic>> {
+        let mut res: FxHashMap<LocalModuleId, Vec<DefDiagnostic>> = FxHashMap::default();
+        for diag in diags {
+            res.entry(diag.in_module).or_default().push(diag);
+        }
+        res
+    }
+
+    /// Counts the diagnostics of `krate`'s `DefMap` by their diagnostic code.
+    pub fn summarize(krate: CrateId, db: &dyn DefDatabase) -> FxHashMap<&'static str, usize> {
+        let mut res: FxHashMap<&'static str, usize> = FxHashMap::default();
+        for diag in db.crate_def_map(krate).diagnostics() {
+            *res.entry(diag.code()).or_default() += 1;
+        }
+        res
+    }
+
+    pub(crate) fn diagnostics_affecting_file_query(
+        db: &dyn DefDatabase,
+        file_id: FileId,
+    ) -> Arc<[DefDiagnostic]> {
+        let mut res = Vec::new();
+        for &krate in db.relevant_crates(file_id).iter() {
+            let def_map = db.crate_def_map(krate);
+            // Inline modules don't have a file of their own, so walk up to the closest module that
+            // does.
+            let defined_in_file = |mut module: LocalModuleId| loop {
+                let data = &def_map[module];
+                if let Some(file) = data.origin.file_id() {
+                    return file == file_id;
+                }
+                match data.parent {
+                    Some(parent) => module = parent,
+                    None => return false,
+                }
+            };
+
+            for diag in &def_map.diagnostics {
+                if !defined_in_file(diag.in_module) || res.contains(diag) {
+                    continue;
+                }
+                let mut points_into_file = false;
+                let mut sink = DiagnosticSinkBuilder::new().build(|it| {
+                    points_into_file |=
+                        it.display_source().file_id.original_file(db.upcast()) == file_id;
+                });
+                diag.add_to(db, diag.in_module, &mut sink);
+                drop(sink);
+                if points_into_file {
+                    res.push(diag.clone());
+                }
+            }
+        }
+        res.into()
+    }
+
     impl DefDiagnostic {
+        /// Converts this diagnostic into its syntax-based form and pushes it into `sink`.
+        pub fn add_to_sink(&self, db: &dyn DefDatabase, sink: &mut DiagnosticSink) {
+            self.add_to(db, self.in_module, sink)
+        }
+
+        /// Returns the message shown to the user for this diagnostic.
+        ///
+        /// Returns `None` if the syntax the diagnostic points to can't be found anymore, like a use
+        /// tree that no longer matches the import it was recorded for.
+        pub fn render_message(&self, db: &dyn DefDatabase) -> Option<String> {
+            let mut message = None;
+            let mut sink = DiagnosticSinkBuilder::new().build(|it| {
+                message.get_or_insert_with(|| it.message());
+            });
+            self.add_to(db, self.in_module, &mut sink);
+            drop(sink);
+            message
+        }
+
+        /// Converts this diagnostic into an `AnyDiagnostic`, which doesn't depend on its kind.
+        ///
+        /// Like `render_message`, this returns `None` if the syntax of the diagnostic can't be
+        /// found.
+        pub fn to_any_diagnostic(&self, db: &dyn DefDatabase) -> Option<AnyDiagnostic> {
+            let (code, message, range) = self.first_report(db)?;
+            Some(AnyDiagnostic {
+                code: code.0,
+                message,
+                severity: self.severity(),
+                file: range.file_id,
+                range: range.value,
+            })
+        }
+
+        /// Returns the range that this diagnostic should be shown at, like the path of an
+        /// unresolved import or the `#[cfg]` attribute of inactive code.
+        ///
+        /// Like `render_message`, this returns `None` if the syntax of the diagnostic can't be
+        /// found.
+        pub fn primary_range(&self, db: &dyn DefDatabase) -> Option<InFile<TextRange>> {
+            self.first_report(db).map(|(_, _, range)| range)
+        }
+
+        fn first_report(
+            &self,
+            db: &dyn DefDatabase,
+        ) -> Option<(DiagnosticCode, String, InFile<TextRange>)> {
+            let mut first = None;
+            let mut sink = DiagnosticSinkBuilder::new().build(|it| {
+                first.get_or_insert_with(|| (it.code(), it.message(), it.display_source()));
+            });
+            self.add_to(db, self.in_module, &mut sink);
+            drop(sink);
+
+            let (code, message, src) = first?;
+            let root = db.parse_or_expand(src.file_id)?;
+            Some((code, message, src.with_value(src.value.to_node(&root).text_range())))
+        }
+
+        /// Returns what this diagnostic is about.
+        pub fn category(&self) -> DiagnosticCategory {
+            self.kind.category()
+        }
+
+        pub fn severity(&self) -> Severity {
+            self.kind.severity()
+        }
+
+        /// Returns a stable, machine-readable code for this kind of diagnostic, like
+        /// `unresolved-import`, for filtering diagnostics and linking to their documentation.
+        pub fn code(&self) -> &'static str {
+            self.kind.code()
+        }
+
+        pub fn is_error(&self) -> bool {
+            self.kind.severity() == Severity::Error
+        }
+
+        pub fn is_warning(&self) -> bool {
+            self.kind.severity() == Severity::Warning
+        }
+
+        pub fn is_weak_warning(&self) -> bool {
+            self.kind.severity() == Severity::WeakWarning
+        }
+
+        /// Whether this diagnostic can only be found for items in block expressions.
+        pub(super) fn is_block_only(&self) -> bool {
+            matches!(self.kind, DiagnosticKind::IneffectiveMacroExport { .. })
+        }
+
         pub(super) fn unresolved_module(
             container: LocalModuleId,
             declaration: AstId<ast::Module>,
@@ -518,6 +1263,22 @@ mod diagnostics {
             }
         }
 
+        pub(super) fn module_inline_and_file(
+            container: LocalModuleId,
+            ast: AstId<ast::Module>,
+            file: String,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::ModuleInlineAndFile { ast, file } }
+        }
+
+        pub(super) fn non_portable_path(
+            container: LocalModuleId,
+            ast: AstId<ast::Module>,
+            path: String,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::NonPortablePath { ast, path } }
+        }
+
         pub(super) fn unresolved_extern_crate(
             container: LocalModuleId,
             declaration: AstId<ast::ExternCrate>,
@@ -528,6 +1289,17 @@ mod diagnostics {
             }
         }
 
+        pub(super) fn redundant_extern_crate_core(
+            container: LocalModuleId,
+            declaration: AstId<ast::ExternCrate>,
+            name: String,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::RedundantExternCrateCore { ast: declaration, name },
+            }
+        }
+
         pub(super) fn unresolved_import(
             container: LocalModuleId,
             ast: AstId<ast::Use>,
@@ -542,7 +1314,10 @@ mod diagnostics {
             cfg: CfgExpr,
             opts: CfgOptions,
         ) -> Self {
-            Self { in_module: container, kind: DiagnosticKind::UnconfiguredCode { ast, cfg, opts } }
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::UnconfiguredCode { ast, cfg, opts: Unhashed(opts) },
+            }
         }
 
         pub(super) fn unresolved_proc_macro(container: LocalModuleId, ast: MacroCallKind) -> Self {
@@ -552,9 +1327,60 @@ mod diagnostics {
         pub(super) fn macro_error(
             container: LocalModuleId,
             ast: MacroCallKind,
-            message: String,
+            error: ExpandError,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::MacroError { ast, error: Unhashed(error) },
+            }
+        }
+
+        pub(super) fn macro_expansion_too_large(
+            container: LocalModuleId,
+            ast: MacroCallKind,
+            token_count: usize,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::MacroExpansionTooLarge { ast, token_count },
+            }
+        }
+
+        pub(super) fn overlapping_impl(
+            container: LocalModuleId,
+            first: AstId<ast::Impl>,
+            second: AstId<ast::Impl>,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::OverlappingImpl { first, second } }
+        }
+
+        pub(super) fn duplicate_lang_item(
+            container: LocalModuleId,
+            first: AstId<ast::Item>,
+            second: AstId<ast::Item>,
+            lang: String,
         ) -> Self {
-            Self { in_module: container, kind: DiagnosticKind::MacroError { ast, message } }
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::DuplicateLangItem { first, second, lang },
+            }
+        }
+
+        pub(super) fn redundant_visibility_on_trait_item(
+            container: LocalModuleId,
+            ast: AstId<ast::Item>,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::RedundantVisibilityOnTraitItem { ast },
+            }
+        }
+
+        pub(super) fn disallowed_nested_impl_trait(
+            container: LocalModuleId,
+            ast: AstId<ast::Fn>,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::DisallowedNestedImplTrait { ast } }
         }
 
         pub(super) fn unresolved_macro_call(
@@ -565,7 +1391,231 @@ mod diagnostics {
             Self { in_module: container, kind: DiagnosticKind::UnresolvedMacroCall { ast, path } }
         }
 
-        pub(super) fn add_to(
+        pub(super) fn macro_used_before_definition(
+            container: LocalModuleId,
+            call: AstId<ast::MacroCall>,
+            def: AstId<ast::Macro>,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::MacroUsedBeforeDefinition { call, def },
+            }
+        }
+
+        pub(super) fn unsupported_macro_position(
+            container: LocalModuleId,
+            ast: AstId<ast::MacroCall>,
+            position: String,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::UnsupportedMacroPosition { ast, position },
+            }
+        }
+
+        pub(super) fn duplicate_macro_definition(
+            container: LocalModuleId,
+            first: AstId<ast::Macro>,
+            second: AstId<ast::Macro>,
+            name: String,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::DuplicateMacroDefinition { first, second, name },
+            }
+        }
+
+        pub(super) fn ineffective_macro_export(
+            container: LocalModuleId,
+            ast: AstId<ast::Macro>,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::IneffectiveMacroExport { ast } }
+        }
+
+        pub(super) fn helper_attr_without_derive(
+            container: LocalModuleId,
+            ast: AstId<ast::Item>,
+            attr: String,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::HelperAttrWithoutDerive { ast, attr },
+            }
+        }
+
+        pub(super) fn prelude_shadow(
+            container: LocalModuleId,
+            ast: AstId<ast::Item>,
+            name: String,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::PreludeShadow { ast, name } }
+        }
+
+        pub(super) fn contradictory_cfg(container: LocalModuleId, ast: AstId<ast::Item>) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::ContradictoryCfg { ast } }
+        }
+
+        pub(super) fn deprecated_without_note(
+            container: LocalModuleId,
+            ast: AstId<ast::Item>,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::DeprecatedWithoutNote { ast } }
+        }
+
+        pub(super) fn empty_enum(container: LocalModuleId, ast: AstId<ast::Enum>) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::EmptyEnum { ast } }
+        }
+
+        pub(super) fn unknown_tool_lint(
+            container: LocalModuleId,
+            ast: AstId<ast::Item>,
+            tool: String,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::UnknownToolLint { ast, tool } }
+        }
+
+        pub(super) fn duplicate_exported_symbol(
+            container: LocalModuleId,
+            ast: AstId<ast::Item>,
+            attr: String,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::DuplicateExportedSymbol { ast, attr },
+            }
+        }
+
+        pub(crate) fn overflowing_literal(
+            container: LocalModuleId,
+            ast: AstId<ast::Item>,
+            ty: String,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::OverflowingLiteral { ast, ty } }
+        }
+
+        pub(crate) fn wrong_number_of_generic_args(
+            container: LocalModuleId,
+            ast: AstId<ast::Item>,
+            path: ModPath,
+            expected: usize,
+            found: usize,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::WrongNumberOfGenericArgs { ast, path, expected, found },
+            }
+        }
+
+        pub(super) fn non_ascii_identifier(
+            container: LocalModuleId,
+            ast: AstId<ast::Item>,
+            name: String,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::NonAsciiIdentifier { ast, name } }
+        }
+
+        pub(super) fn unresolved_derive_path(
+            container: LocalModuleId,
+            ast: AstId<ast::Item>,
+            path: ModPath,
+            failed_segment: usize,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::UnresolvedDerivePath { ast, path, failed_segment },
+            }
+        }
+
+        pub(super) fn invalid_repr(
+            container: LocalModuleId,
+            ast: AstId<ast::Item>,
+            value: String,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::InvalidRepr { ast, value } }
+        }
+
+        pub(super) fn bad_self_import(
+            container: LocalModuleId,
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::BadSelfImport { id, index } }
+        }
+
+        pub(super) fn self_referential_import(
+            container: LocalModuleId,
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::SelfReferentialImport { id, index } }
+        }
+
+        pub(super) fn super_beyond_root(
+            container: LocalModuleId,
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::SuperBeyondRoot { id, index } }
+        }
+
+        pub(super) fn glob_of_self(
+            container: LocalModuleId,
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::GlobOfSelf { id, index } }
+        }
+
+        pub(super) fn crate_self_alias(
+            container: LocalModuleId,
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+            name: String,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::CrateSelfAlias { id, index, name } }
+        }
+
+        pub(super) fn reexport_of_private_module_item(
+            container: LocalModuleId,
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+            module: ModuleId,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::ReExportOfPrivateModuleItem { id, index, module },
+            }
+        }
+
+        pub(crate) fn unused_import(
+            container: LocalModuleId,
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::UnusedImport { id, index } }
+        }
+
+        pub(super) fn unresolved_trait_import(
+            container: LocalModuleId,
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+        ) -> Self {
+            Self { in_module: container, kind: DiagnosticKind::UnresolvedTraitImport { id, index } }
+        }
+
+        pub(super) fn glob_overrides_prelude(
+            container: LocalModuleId,
+            id: ItemTreeId<item_tree::Import>,
+            index: usize,
+            name: String,
+        ) -> Self {
+            Self {
+                in_module: container,
+                kind: DiagnosticKind::GlobOverridesPrelude { id, index, name },
+            }
+        }
+
+        pub(super) fn add_to(
             &self,
             db: &dyn DefDatabase,
             target_module: LocalModuleId,
@@ -585,6 +1635,27 @@ mod diagnostics {
                     })
                 }
 
+                DiagnosticKind::ModuleInlineAndFile { ast, file } => {
+                    let decl = ast.to_node(db.upcast());
+                    sink.push(ModuleInlineAndFile {
+                        file: ast.file_id,
+                        decl: AstPtr::new(&decl),
+                        path: file.clone(),
+                    })
+                }
+
+                DiagnosticKind::NonPortablePath { ast, path } => {
+                    let decl = ast.to_node(db.upcast());
+                    let node = match decl
+                        .attrs()
+                        .find(|attr| attr.simple_name().as_deref() == Some("path"))
+                    {
+                        Some(attr) => SyntaxNodePtr::new(attr.syntax()),
+                        None => SyntaxNodePtr::new(decl.syntax()),
+                    };
+                    sink.push(NonPortablePath { file: ast.file_id, node, path: path.clone() });
+                }
+
                 DiagnosticKind::UnresolvedExternCrate { ast } => {
                     let item = ast.to_node(db.upcast());
                     sink.push(UnresolvedExternCrate {
@@ -593,24 +1664,18 @@ mod diagnostics {
                     });
                 }
 
+                DiagnosticKind::RedundantExternCrateCore { ast, name } => {
+                    let item = ast.to_node(db.upcast());
+                    sink.push(RedundantExternCrateCore {
+                        file: ast.file_id,
+                        item: AstPtr::new(&item),
+                        name: name.clone(),
+                    });
+                }
+
                 DiagnosticKind::UnresolvedImport { ast, index } => {
                     let use_item = ast.to_node(db.upcast());
-                    let hygiene = Hygiene::new(db.upcast(), ast.file_id);
-                    let mut cur = 0;
-                    let mut tree = None;
-                    ModPath::expand_use_item(
-                        InFile::new(ast.file_id, use_item),
-                        &hygiene,
-                        |_mod_path, use_tree, _is_glob, _alias| {
-                            if cur == *index {
-                                tree = Some(use_tree.clone());
-                            }
-
-                            cur += 1;
-                        },
-                    );
-
-                    if let Some(tree) = tree {
+                    if let Some(tree) = use_tree_at(db, ast.with_value(use_item), *index) {
                         sink.push(UnresolvedImport { file: ast.file_id, node: AstPtr::new(&tree) });
                     }
                 }
@@ -621,62 +1686,129 @@ mod diagnostics {
                         file: ast.file_id,
                         node: AstPtr::new(&item).into(),
                         cfg: cfg.clone(),
-                        opts: opts.clone(),
+                        opts: opts.0.clone(),
                     });
                 }
 
                 DiagnosticKind::UnresolvedProcMacro { ast } => {
-                    let mut precise_location = None;
-                    let (file, ast, name) = match ast {
-                        MacroCallKind::FnLike { ast_id } => {
-                            let node = ast_id.to_node(db.upcast());
-                            (ast_id.file_id, SyntaxNodePtr::from(AstPtr::new(&node)), None)
-                        }
+                    let src = macro_call_source(db, ast);
+                    let (precise_location, name) = match ast {
+                        MacroCallKind::FnLike { .. } => (None, None),
                         MacroCallKind::Derive { ast_id, derive_name, .. } => {
-                            let node = ast_id.to_node(db.upcast());
-
                             // Compute the precise location of the macro name's token in the derive
                             // list.
-                            // FIXME: This does not handle paths to the macro, but neither does the
-                            // rest of r-a.
-                            let derive_attrs =
-                                node.attrs().filter_map(|attr| match attr.as_simple_call() {
-                                    Some((name, args)) if name == "derive" => Some(args),
-                                    _ => None,
-                                });
-                            'outer: for attr in derive_attrs {
-                                let tokens =
-                                    attr.syntax().children_with_tokens().filter_map(|elem| {
-                                        match elem {
-                                            syntax::NodeOrToken::Node(_) => None,
-                                            syntax::NodeOrToken::Token(tok) => Some(tok),
-                                        }
-                                    });
-                                for token in tokens {
-                                    if token.kind() == SyntaxKind::IDENT
-                                        && token.text() == derive_name.as_str()
-                                    {
-                                        precise_location = Some(token.text_range());
-                                        break 'outer;
-                                    }
-                                }
-                            }
-
-                            (
-                                ast_id.file_id,
-                                SyntaxNodePtr::from(AstPtr::new(&node)),
-                                Some(derive_name.clone()),
-                            )
+                            let precise_location = ast_id
+                                .to_node(db.upcast())
+                                .attrs()
+                                .find_map(|attr| derive_name_token(&attr, derive_name))
+                                .map(|token| token.text_range());
+                            (precise_location, Some(derive_name.clone()))
                         }
                     };
                     sink.push(UnresolvedProcMacro {
-                        file,
-                        node: ast,
+                        file: src.file_id,
+                        node: src.value,
                         precise_location,
                         macro_name: name,
                     });
                 }
 
+                DiagnosticKind::MacroExpansionTooLarge { ast, token_count } => {
+                    let src = macro_call_source(db, ast);
+                    sink.push(MacroExpansionTooLarge {
+                        file: src.file_id,
+                        node: src.value,
+                        token_count: *token_count,
+                    });
+                }
+
+                DiagnosticKind::DuplicateLangItem { first, second, lang } => {
+                    let first_item = first.to_node(db.upcast());
+                    let second_item = second.to_node(db.upcast());
+                    let node = match second_item
+                        .attrs()
+                        .find(|attr| attr.simple_name().as_deref() == Some("lang"))
+                    {
+                        Some(attr) => SyntaxNodePtr::new(attr.syntax()),
+                        None => SyntaxNodePtr::new(second_item.syntax()),
+                    };
+                    sink.push(DuplicateLangItem {
+                        file: second.file_id,
+                        node,
+                        lang: lang.clone(),
+                        first: first.with_value(AstPtr::new(&first_item)),
+                    });
+                }
+
+                DiagnosticKind::RedundantVisibilityOnTraitItem { ast } => {
+                    let item = ast.to_node(db.upcast());
+                    let node = match item.syntax().children().find_map(ast::Visibility::cast) {
+                        Some(vis) => SyntaxNodePtr::new(vis.syntax()),
+                        None => SyntaxNodePtr::new(item.syntax()),
+                    };
+                    sink.push(RedundantVisibilityOnTraitItem { file: ast.file_id, node });
+                }
+
+                DiagnosticKind::DisallowedNestedImplTrait { ast } => {
+                    let func = ast.to_node(db.upcast());
+                    let signature = func
+                        .param_list()
+                        .map(|it| it.syntax().clone())
+                        .into_iter()
+                        .chain(func.ret_type().map(|it| it.syntax().clone()));
+                    let mut reported = false;
+                    for node in signature.flat_map(|it| it.descendants()) {
+                        let impl_trait = match ast::ImplTraitType::cast(node) {
+                            Some(it) => it,
+                            None => continue,
+                        };
+                        // The nearest generic argument below an enclosing `impl Trait` decides:
+                        // `impl Iterator<Item = impl Debug>` is fine, `impl Into<impl Debug>`
+                        // isn't.
+                        let mut in_type_arg = false;
+                        let nested = impl_trait.syntax().ancestors().skip(1).any(|it| {
+                            match it.kind() {
+                                SyntaxKind::TYPE_ARG => in_type_arg = true,
+                                SyntaxKind::ASSOC_TYPE_ARG => in_type_arg = false,
+                                SyntaxKind::IMPL_TRAIT_TYPE => return in_type_arg,
+                                _ => {}
+                            }
+                            false
+                        });
+                        if nested {
+                            let node = SyntaxNodePtr::new(impl_trait.syntax());
+                            sink.push(DisallowedNestedImplTrait { file: ast.file_id, node });
+                            reported = true;
+                        }
+                    }
+                    if !reported {
+                        let node = SyntaxNodePtr::new(func.syntax());
+                        sink.push(DisallowedNestedImplTrait { file: ast.file_id, node });
+                    }
+                }
+
+                DiagnosticKind::OverlappingImpl { first, second } => {
+                    let first_impl = first.to_node(db.upcast());
+                    let second_impl = second.to_node(db.upcast());
+                    // Highlight the header of the impl, up to its self type.
+                    let precise_location = second_impl.self_ty().map(|ty| {
+                        TextRange::new(
+                            second_impl.syntax().text_range().start(),
+                            ty.syntax().text_range().end(),
+                        )
+                    });
+                    sink.push(OverlappingImpl {
+                        file: second.file_id,
+                        node: AstPtr::new(&second_impl),
+                        precise_location,
+                        first: first.with_value(AstPtr::new(&first_impl)),
+                        trait_: second_impl.trait_().map_or_else(String::new, |it| it.to_string()),
+                        self_ty: second_impl
+                            .self_ty()
+                            .map_or_else(String::new, |it| it.to_string()),
+                    });
+                }
+
                 DiagnosticKind::UnresolvedMacroCall { ast, path } => {
                     let node = ast.to_node(db.upcast());
                     sink.push(UnresolvedMacroCall {
@@ -686,20 +1818,448 @@ mod diagnostics {
                     });
                 }
 
-                DiagnosticKind::MacroError { ast, message } => {
-                    let (file, ast) = match ast {
-                        MacroCallKind::FnLike { ast_id, .. } => {
-                            let node = ast_id.to_node(db.upcast());
-                            (ast_id.file_id, SyntaxNodePtr::from(AstPtr::new(&node)))
-                        }
-                        MacroCallKind::Derive { ast_id, .. } => {
-                            let node = ast_id.to_node(db.upcast());
-                            (ast_id.file_id, SyntaxNodePtr::from(AstPtr::new(&node)))
-                        }
+                DiagnosticKind::MacroUsedBeforeDefinition { call, def } => {
+                    let call_node = call.to_node(db.upcast());
+                    let def_node = def.to_node(db.upcast());
+                    // The item tree only records `macro_rules!` definitions that have a name.
+                    let name =
+                        def_node.name().map(|name| name.text().to_string()).unwrap_or_default();
+                    sink.push(MacroUsedBeforeDefinition {
+                        file: call.file_id,
+                        node: AstPtr::new(&call_node),
+                        name,
+                        def: InFile::new(def.file_id, AstPtr::new(&def_node)),
+                    });
+                }
+
+                DiagnosticKind::DuplicateMacroDefinition { first, second, name } => {
+                    let first_node = first.to_node(db.upcast());
+                    let second_node = second.to_node(db.upcast());
+                    sink.push(DuplicateMacroDefinition {
+                        file: second.file_id,
+                        node: AstPtr::new(&second_node),
+                        name: name.clone(),
+                        first: InFile::new(first.file_id, AstPtr::new(&first_node)),
+                    });
+                }
+
+                DiagnosticKind::IneffectiveMacroExport { ast } => {
+                    let node = ast.to_node(db.upcast());
+                    let node = match node
+                        .attrs()
+                        .find(|attr| attr.simple_name().as_deref() == Some("macro_export"))
+                    {
+                        Some(attr) => SyntaxNodePtr::new(attr.syntax()),
+                        None => SyntaxNodePtr::new(node.syntax()),
+                    };
+                    sink.push(IneffectiveMacroExport { file: ast.file_id, node });
+                }
+
+                DiagnosticKind::UnsupportedMacroPosition { ast, position } => {
+                    let node = ast.to_node(db.upcast());
+                    sink.push(UnsupportedMacroPosition {
+                        file: ast.file_id,
+                        node: AstPtr::new(&node),
+                        position: position.clone(),
+                    });
+                }
+
+                DiagnosticKind::MacroError { ast, error } => {
+                    let src = macro_call_source(db, ast);
+                    sink.push(MacroError::new(src.file_id, src.value, &error.0));
+                }
+
+                DiagnosticKind::ContradictoryCfg { ast } => {
+                    let item = ast.to_node(db.upcast());
+                    let node = match item
+                        .attrs()
+                        .find(|attr| attr.simple_name().as_deref() == Some("cfg"))
+                    {
+                        Some(attr) => SyntaxNodePtr::new(attr.syntax()),
+                        None => SyntaxNodePtr::new(item.syntax()),
+                    };
+                    sink.push(ContradictoryCfg { file: ast.file_id, node });
+                }
+
+                DiagnosticKind::DeprecatedWithoutNote { ast } => {
+                    let item = ast.to_node(db.upcast());
+                    let node = match item
+                        .attrs()
+                        .find(|attr| attr.simple_name().as_deref() == Some("deprecated"))
+                    {
+                        Some(attr) => SyntaxNodePtr::new(attr.syntax()),
+                        None => SyntaxNodePtr::new(item.syntax()),
                     };
-                    sink.push(MacroError { file, node: ast, message: message.clone() });
+                    sink.push(DeprecatedWithoutNote { file: ast.file_id, node });
+                }
+
+                DiagnosticKind::EmptyEnum { ast } => {
+                    let enum_ = ast.to_node(db.upcast());
+                    let node = match enum_.name() {
+                        Some(name) => SyntaxNodePtr::new(name.syntax()),
+                        None => SyntaxNodePtr::new(enum_.syntax()),
+                    };
+                    sink.push(EmptyEnum { file: ast.file_id, node });
+                }
+
+                DiagnosticKind::UnknownToolLint { ast, tool } => {
+                    let item = ast.to_node(db.upcast());
+                    // Point at the tool segment of the first attribute that uses it.
+                    let node = item
+                        .attrs()
+                        .filter_map(|attr| attr.path())
+                        .filter(|path| path.qualifier().is_some())
+                        .filter_map(|path| {
+                            iter::successors(Some(path), |it| it.qualifier()).last()?.segment()
+                        })
+                        .find(|segment| segment.syntax().text() == tool.as_str());
+                    let node = match node {
+                        Some(segment) => SyntaxNodePtr::new(segment.syntax()),
+                        None => SyntaxNodePtr::new(item.syntax()),
+                    };
+                    sink.push(UnknownToolLint { file: ast.file_id, node, tool: tool.clone() });
+                }
+
+                DiagnosticKind::HelperAttrWithoutDerive { ast, attr } => {
+                    let item = ast.to_node(db.upcast());
+                    // The helper attribute may be on the item itself or on one of its fields or
+                    // variants.
+                    let node = item
+                        .syntax()
+                        .descendants()
+                        .filter_map(ast::Attr::cast)
+                        .find(|it| it.simple_name().as_deref() == Some(attr.as_str()));
+                    let node = match node {
+                        Some(it) => SyntaxNodePtr::new(it.syntax()),
+                        None => SyntaxNodePtr::new(item.syntax()),
+                    };
+                    sink.push(HelperAttrWithoutDerive {
+                        file: ast.file_id,
+                        node,
+                        attr: attr.clone(),
+                    });
+                }
+
+                DiagnosticKind::DuplicateExportedSymbol { ast, attr } => {
+                    let item = ast.to_node(db.upcast());
+                    let node = match item
+                        .attrs()
+                        .find(|it| it.simple_name().as_deref() == Some(attr.as_str()))
+                    {
+                        Some(it) => SyntaxNodePtr::new(it.syntax()),
+                        None => SyntaxNodePtr::new(item.syntax()),
+                    };
+                    sink.push(DuplicateExportedSymbol {
+                        file: ast.file_id,
+                        node,
+                        attr: attr.clone(),
+                    });
+                }
+
+                DiagnosticKind::OverflowingLiteral { ast, ty } => {
+                    let body = match ast.to_node(db.upcast()) {
+                        ast::Item::Const(it) => it.body(),
+                        ast::Item::Static(it) => it.body(),
+                        _ => None,
+                    };
+                    if let Some(body) = body {
+                        sink.push(OverflowingLiteral {
+                            file: ast.file_id,
+                            node: AstPtr::new(&body),
+                            ty: ty.clone(),
+                        });
+                    }
+                }
+
+                DiagnosticKind::WrongNumberOfGenericArgs { ast, path, expected, found } => {
+                    let item = ast.to_node(db.upcast());
+                    let hygiene = Hygiene::new(db.upcast(), ast.file_id);
+                    // Point at the generic arguments of the offending path.
+                    let node = item
+                        .syntax()
+                        .descendants()
+                        .filter_map(ast::PathType::cast)
+                        .filter_map(|it| it.path())
+                        .filter(|it| ModPath::from_src(it.clone(), &hygiene).as_ref() == Some(path))
+                        .filter_map(|it| it.segment()?.generic_arg_list())
+                        .find(|it| {
+                            let type_args = it
+                                .generic_args()
+                                .filter(|it| matches!(it, ast::GenericArg::TypeArg(_)))
+                                .count();
+                            type_args == *found
+                        });
+                    let node = match node {
+                        Some(it) => SyntaxNodePtr::new(it.syntax()),
+                        None => SyntaxNodePtr::new(item.syntax()),
+                    };
+                    sink.push(WrongNumberOfGenericArgs {
+                        file: ast.file_id,
+                        node,
+                        expected: *expected,
+                        found: *found,
+                    });
                 }
+
+                DiagnosticKind::PreludeShadow { ast, name } => {
+                    let item = ast.to_node(db.upcast());
+                    // Point at the name of the item if it has one, rather than at the whole item.
+                    let node = match item.syntax().children().find_map(ast::Name::cast) {
+                        Some(it) => SyntaxNodePtr::new(it.syntax()),
+                        None => SyntaxNodePtr::new(item.syntax()),
+                    };
+                    sink.push(PreludeShadow { file: ast.file_id, node, name: name.clone() });
+                }
+
+                DiagnosticKind::NonAsciiIdentifier { ast, name } => {
+                    let item = ast.to_node(db.upcast());
+                    let node = match item.syntax().children().find_map(ast::Name::cast) {
+                        Some(it) => SyntaxNodePtr::new(it.syntax()),
+                        None => SyntaxNodePtr::new(item.syntax()),
+                    };
+                    sink.push(NonAsciiIdentifier { file: ast.file_id, node, name: name.clone() });
+                }
+
+                DiagnosticKind::UnresolvedDerivePath { ast, path, failed_segment } => {
+                    let item = ast.to_node(db.upcast());
+                    let segments: Vec<_> =
+                        path.segments().iter().map(|it| it.to_string()).collect();
+                    let (node, precise_location) = match item.attrs().find_map(|attr| {
+                        let tokens = derive_path_tokens(&attr, &segments)?;
+                        Some((attr, tokens))
+                    }) {
+                        Some((attr, tokens)) => (
+                            SyntaxNodePtr::new(attr.syntax()),
+                            tokens.get(*failed_segment).map(|token| token.text_range()),
+                        ),
+                        None => (SyntaxNodePtr::new(item.syntax()), None),
+                    };
+                    sink.push(UnresolvedDerivePath {
+                        file: ast.file_id,
+                        node,
+                        precise_location,
+                        path: path.clone(),
+                        failed_segment: *failed_segment,
+                    });
+                }
+
+                DiagnosticKind::InvalidRepr { ast, value } => {
+                    let item = ast.to_node(db.upcast());
+                    // Point at the `#[repr]` attribute containing the hint, if it can be found.
+                    let node =
+                        match item.attrs().find(|attr| repr_hint_token(attr, value).is_some()) {
+                            Some(attr) => SyntaxNodePtr::new(attr.syntax()),
+                            None => SyntaxNodePtr::new(item.syntax()),
+                        };
+                    sink.push(InvalidRepr { file: ast.file_id, node, value: value.clone() });
+                }
+
+                DiagnosticKind::UnresolvedTraitImport { id, index } => {
+                    let item_tree = id.item_tree(db);
+                    let import = &item_tree[id.value];
+                    let name = match import.path.segments().last() {
+                        Some(name) => name.to_string(),
+                        None => import.path.to_string(),
+                    };
+                    let use_item = InFile::new(id.file_id(), import.ast_id).to_node(db.upcast());
+                    if let Some(tree) = use_tree_at(db, InFile::new(id.file_id(), use_item), *index)
+                    {
+                        sink.push(UnresolvedTraitImport {
+                            file: id.file_id(),
+                            node: AstPtr::new(&tree),
+                            name,
+                        });
+                    }
+                }
+
+                DiagnosticKind::BadSelfImport { id, index } => {
+                    let item_tree = id.item_tree(db);
+                    let ast = InFile::new(id.file_id(), item_tree[id.value].ast_id);
+                    let use_item = ast.to_node(db.upcast());
+                    if let Some(tree) = use_tree_at(db, ast.with_value(use_item), *index) {
+                        sink.push(BadSelfImport { file: id.file_id(), node: AstPtr::new(&tree) });
+                    }
+                }
+
+                DiagnosticKind::SuperBeyondRoot { id, index } => {
+                    let item_tree = id.item_tree(db);
+                    let ast = InFile::new(id.file_id(), item_tree[id.value].ast_id);
+                    let use_item = ast.to_node(db.upcast());
+                    if let Some(tree) = use_tree_at(db, ast.with_value(use_item), *index) {
+                        sink.push(SuperBeyondRoot { file: id.file_id(), node: AstPtr::new(&tree) });
+                    }
+                }
+
+                DiagnosticKind::GlobOfSelf { id, index } => {
+                    let item_tree = id.item_tree(db);
+                    let ast = InFile::new(id.file_id(), item_tree[id.value].ast_id);
+                    let use_item = ast.to_node(db.upcast());
+                    if let Some(tree) = use_tree_at(db, ast.with_value(use_item), *index) {
+                        sink.push(GlobOfSelf { file: id.file_id(), node: AstPtr::new(&tree) });
+                    }
+                }
+
+                DiagnosticKind::CrateSelfAlias { id, index, name } => {
+                    let item_tree = id.item_tree(db);
+                    let ast = InFile::new(id.file_id(), item_tree[id.value].ast_id);
+                    let use_item = ast.to_node(db.upcast());
+                    if let Some(tree) = use_tree_at(db, ast.with_value(use_item), *index) {
+                        sink.push(CrateSelfAlias {
+                            file: id.file_id(),
+                            node: AstPtr::new(&tree),
+                            name: name.clone(),
+                        });
+                    }
+                }
+
+                DiagnosticKind::SelfReferentialImport { id, index } => {
+                    let item_tree = id.item_tree(db);
+                    let import = &item_tree[id.value];
+                    let name = match import.path.segments().last() {
+                        Some(name) => name.to_string(),
+                        None => import.path.to_string(),
+                    };
+                    let ast = InFile::new(id.file_id(), import.ast_id);
+                    let use_item = ast.to_node(db.upcast());
+                    if let Some(tree) = use_tree_at(db, ast.with_value(use_item), *index) {
+                        sink.push(SelfReferentialImport {
+                            file: id.file_id(),
+                            node: AstPtr::new(&tree),
+                            name,
+                        });
+                    }
+                }
+
+                DiagnosticKind::ReExportOfPrivateModuleItem { id, index, module } => {
+                    let def_map = module.def_map(db);
+                    let module_name = def_map[module.local_id].parent.and_then(|parent| {
+                        def_map[parent].children.iter().find_map(|(name, &child)| {
+                            if child == module.local_id {
+                                Some(name.to_string())
+                            } else {
+                                None
+                            }
+                        })
+                    });
+                    // The crate root has no name, refer to it as `crate`.
+                    let module_name = module_name.unwrap_or_else(|| "crate".to_string());
+
+                    let item_tree = id.item_tree(db);
+                    let ast = InFile::new(id.file_id(), item_tree[id.value].ast_id);
+                    let use_item = ast.to_node(db.upcast());
+                    if let Some(tree) = use_tree_at(db, ast.with_value(use_item), *index) {
+                        sink.push(ReExportOfPrivateModuleItem {
+                            file: id.file_id(),
+                            node: AstPtr::new(&tree),
+                            module: module_name,
+                        });
+                    }
+                }
+
+                DiagnosticKind::UnusedImport { id, index } => {
+                    let item_tree = id.item_tree(db);
+                    let ast = InFile::new(id.file_id(), item_tree[id.value].ast_id);
+                    let use_item = ast.to_node(db.upcast());
+                    if let Some(tree) = use_tree_at(db, ast.with_value(use_item), *index) {
+                        sink.push(UnusedImport { file: id.file_id(), node: AstPtr::new(&tree) });
+                    }
+                }
+
+                DiagnosticKind::GlobOverridesPrelude { id, index, name } => {
+                    let item_tree = id.item_tree(db);
+                    let ast = InFile::new(id.file_id(), item_tree[id.value].ast_id);
+                    let use_item = ast.to_node(db.upcast());
+                    if let Some(tree) = use_tree_at(db, ast.with_value(use_item), *index) {
+                        sink.push(GlobOverridesPrelude {
+                            file: id.file_id(),
+                            node: AstPtr::new(&tree),
+                            name: name.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the syntax a macro call should be reported at: the whole call of a function-like
+    /// macro, or the item a derive macro is applied to.
+    pub(super) fn macro_call_source(
+        db: &dyn DefDatabase,
+        kind: &MacroCallKind,
+    ) -> InFile<SyntaxNodePtr> {
+        match kind {
+            MacroCallKind::FnLike { ast_id } => {
+                let node = ast_id.to_node(db.upcast());
+                InFile::new(ast_id.file_id, SyntaxNodePtr::from(AstPtr::new(&node)))
             }
+            MacroCallKind::Derive { ast_id, .. } => {
+                let node = ast_id.to_node(db.upcast());
+                InFile::new(ast_id.file_id, SyntaxNodePtr::from(AstPtr::new(&node)))
+            }
+        }
+    }
+
+    /// Finds `derive_name` in the list of a `#[derive]` attribute.
+    // FIXME: This does not handle paths to the macro, but neither does the rest of r-a.
+    fn derive_name_token(attr: &ast::Attr, derive_name: &str) -> Option<SyntaxToken> {
+        match attr.as_simple_call() {
+            Some((name, args)) if name == "derive" => args
+                .syntax()
+                .children_with_tokens()
+                .filter_map(|elem| elem.into_token())
+                .find(|token| token.kind() == SyntaxKind::IDENT && token.text() == derive_name),
+            _ => None,
+        }
+    }
+
+    /// Finds the path with the given `segments` in the list of a `#[derive]` attribute, and returns
+    /// the tokens of its segments.
+    fn derive_path_tokens(attr: &ast::Attr, segments: &[String]) -> Option<Vec<SyntaxToken>> {
+        let args = match attr.as_simple_call() {
+            Some((name, args)) if name == "derive" => args,
+            _ => return None,
+        };
+        let tokens: Vec<_> =
+            args.syntax().children_with_tokens().filter_map(|elem| elem.into_token()).collect();
+        tokens
+            .split(|token| token.kind() == SyntaxKind::COMMA)
+            .map(|path| {
+                path.iter().filter(|token| token.kind() == SyntaxKind::IDENT).cloned().collect()
+            })
+            .find(|path: &Vec<SyntaxToken>| {
+                path.len() == segments.len()
+                    && path.iter().zip(segments).all(|(token, segment)| token.text() == segment)
+            })
+    }
+
+    /// Finds the hint `value` in the list of a `#[repr]` attribute.
+    fn repr_hint_token(attr: &ast::Attr, value: &str) -> Option<SyntaxToken> {
+        match attr.as_simple_call() {
+            Some((name, args)) if name == "repr" => args
+                .syntax()
+                .children_with_tokens()
+                .filter_map(|elem| elem.into_token())
+                .find(|token| token.kind() == SyntaxKind::IDENT && token.text() == value),
+            _ => None,
         }
     }
+
+    /// Returns the `UseTree` visited at position `index` by `ModPath::expand_use_item`.
+    fn use_tree_at(
+        db: &dyn DefDatabase,
+        use_item: InFile<ast::Use>,
+        index: usize,
+    ) -> Option<ast::UseTree> {
+        let hygiene = Hygiene::new(db.upcast(), use_item.file_id);
+        let mut cur = 0;
+        let mut tree = None;
+        ModPath::expand_use_item(use_item, &hygiene, |_mod_path, use_tree, _is_glob, _alias| {
+            if cur == index {
+                tree = Some(use_tree.clone());
+            }
+
+            cur += 1;
+        });
+        tree
+    }
 }
@@ -3,15 +3,15 @@
 //! `DefCollector::collect` contains the fixed-point iteration loop which
 //! resolves imports and expands macros.
 
-use std::iter;
+use std::{collections::hash_map::Entry, iter};
 
 use base_db::{CrateId, FileId, ProcMacroId};
-use cfg::{CfgExpr, CfgOptions};
+use cfg::{CfgAtom, CfgExpr, CfgOptions, DnfExpr};
 use hir_expand::{
     ast_id_map::FileAstId,
     builtin_derive::find_builtin_derive,
     builtin_macro::find_builtin_macro,
-    name::{AsName, Name},
+    name::{name, AsName, Name},
     proc_macro::ProcMacroExpander,
     AttrId, HirFileId, MacroCallId, MacroCallKind, MacroDefId, MacroDefKind,
 };
@@ -21,21 +21,25 @@ use syntax::ast;
 
 use crate::{
     attr::Attrs,
+    builtin_type::{BuiltinInt, BuiltinUint},
     db::DefDatabase,
     derive_macro_as_call_id,
     intern::Interned,
-    item_scope::{ImportType, PerNsGlobImports},
+    item_scope::{ImportType, Namespace, PerNsGlobImports},
     item_tree::{
-        self, FileItemTreeId, ItemTree, ItemTreeId, MacroCall, MacroDef, MacroRules, Mod, ModItem,
-        ModKind, StructDefKind,
+        self, Fields, FileItemTreeId, FnFlags, ItemTree, ItemTreeId, MacroCall, MacroDef,
+        MacroRules, Mod, ModItem, ModKind, Param, StructDefKind,
     },
     macro_call_as_call_id,
     nameres::{
-        diagnostics::DefDiagnostic, mod_resolution::ModDir, path_resolution::ReachedFixedPoint,
+        diagnostics::{DedupDiagnostics, DefDiagnostic, Severity},
+        mod_resolution::ModDir,
+        path_resolution::ReachedFixedPoint,
         BuiltinShadowMode, DefMap, ModuleData, ModuleOrigin, ResolveMode,
     },
-    path::{ImportAlias, ModPath, PathKind},
+    path::{GenericArg, ImportAlias, ModPath, PathKind},
     per_ns::PerNs,
+    type_ref::{TypeBound, TypeRef},
     visibility::{RawVisibility, Visibility},
     AdtId, AstId, AstIdWithPath, ConstLoc, EnumLoc, EnumVariantId, FunctionLoc, ImplLoc, Intern,
     LocalModuleId, ModuleDefId, StaticLoc, StructLoc, TraitLoc, TypeAliasLoc, UnionLoc,
@@ -49,9 +53,34 @@ const EXPANSION_DEPTH_LIMIT: usize = 128;
 const FIXED_POINT_LIMIT: usize = 8192;
 
 pub(super) fn collect_defs(
+    db: &dyn DefDatabase,
+    def_map: DefMap,
+    block: Option<AstId<ast::BlockExpr>>,
+) -> DefMap {
+    collect_defs_with(db, def_map, block, DiagnosticStream::default())
+}
+
+/// Like `collect_defs`, but passes every diagnostic to `on_diagnostic` as soon as it's found
+/// instead of storing it in the returned `DefMap`.
+pub(super) fn collect_defs_streaming(
+    db: &dyn DefDatabase,
+    def_map: DefMap,
+    min_severity: Severity,
+    on_diagnostic: &mut dyn FnMut(DefDiagnostic),
+) -> DefMap {
+    let diagnostics = DiagnosticStream {
+        on_diagnostic: Some(Box::new(on_diagnostic)),
+        min_severity: Some(min_severity),
+        stored: DedupDiagnostics::default(),
+    };
+    collect_defs_with(db, def_map, None, diagnostics)
+}
+
+fn collect_defs_with(
     db: &dyn DefDatabase,
     mut def_map: DefMap,
     block: Option<AstId<ast::BlockExpr>>,
+    diagnostics: DiagnosticStream<'_>,
 ) -> DefMap {
     let crate_graph = db.crate_graph();
 
@@ -99,6 +128,11 @@ pub(super) fn collect_defs(
         proc_macros,
         exports_proc_macros: false,
         from_glob_import: Default::default(),
+        tool_attrs: Vec::new(),
+        macro_rules_defs: FxHashMap::default(),
+        lang_items: FxHashMap::default(),
+        exported_symbols: FxHashMap::default(),
+        diagnostics,
     };
     match block {
         Some(block) => {
@@ -197,6 +231,36 @@ impl Import {
             source: ImportSource::ExternCrate(id),
         }
     }
+
+    /// The name this import defines in its module. Glob and `_` imports don't define a name.
+    fn defined_name(&self) -> Option<&Name> {
+        if self.is_glob {
+            return None;
+        }
+        match &self.alias {
+            Some(ImportAlias::Alias(alias)) => Some(alias),
+            Some(ImportAlias::Underscore) => None,
+            None => self.path.segments().last(),
+        }
+    }
+
+    /// Whether this import looks up the name it defines in its own module, like `use self::X;`.
+    ///
+    /// Plain paths like `use X;` are not considered, as they might also refer to a missing crate.
+    fn is_self_referential(&self) -> bool {
+        if self.is_glob || self.is_extern_crate || self.path.kind != PathKind::Super(0) {
+            return false;
+        }
+        let name = match self.path.segments() {
+            [name] => name,
+            _ => return false,
+        };
+        match &self.alias {
+            Some(ImportAlias::Alias(alias)) => alias == name,
+            Some(ImportAlias::Underscore) => false,
+            None => true,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -243,6 +307,57 @@ struct DefCollector<'a> {
     proc_macros: Vec<(Name, ProcMacroExpander)>,
     exports_proc_macros: bool,
     from_glob_import: PerNsGlobImports,
+    /// Attributes like `#[tool::attr]` whose first segment isn't a known tool, together with that
+    /// segment. They are only reported once it's clear that the segment doesn't resolve to a
+    /// module either.
+    tool_attrs: Vec<(LocalModuleId, AstId<ast::Item>, Name)>,
+    /// The `macro_rules!` macros written directly in each module, to find ones that are defined
+    /// twice.
+    macro_rules_defs: FxHashMap<(LocalModuleId, Name), AstId<ast::Macro>>,
+    /// The first item claiming each `#[lang = "..."]` item, to find ones that are claimed twice.
+    lang_items: FxHashMap<String, AstId<ast::Item>>,
+    /// The first item exporting each symbol with `#[no_mangle]` or `#[export_name]`, to find
+    /// symbols that are exported twice. Block `DefMap`s start out empty, so items in blocks are
+    /// only compared with the other items of their block.
+    exported_symbols: FxHashMap<String, AstId<ast::Item>>,
+    diagnostics: DiagnosticStream<'a>,
+}
+
+/// Receives the diagnostics found during collection. They are stored in the `DefMap` unless a
+/// callback is set, in which case they are passed to it one by one.
+#[derive(Default)]
+struct DiagnosticStream<'a> {
+    on_diagnostic: Option<Box<dyn FnMut(DefDiagnostic) + 'a>>,
+    /// Diagnostics less severe than this are dropped. All of them are kept if it's `None`.
+    min_severity: Option<Severity>,
+    stored: DedupDiagnostics,
+}
+
+impl DiagnosticStream<'_> {
+    /// Whether diagnostics of `severity` are kept, so that checks which can only find dropped
+    /// diagnostics can be skipped.
+    fn reports(&self, severity: Severity) -> bool {
+        match self.min_severity {
+            Some(min) => severity >= min,
+            None => true,
+        }
+    }
+
+    fn push(&mut self, diag: DefDiagnostic) {
+        if !self.reports(diag.severity()) {
+            return;
+        }
+        match &mut self.on_diagnostic {
+            Some(on_diagnostic) => on_diagnostic(diag),
+            None => {
+                self.stored.push(diag);
+            }
+        }
+    }
+
+    fn extend(&mut self, diags: impl IntoIterator<Item = DefDiagnostic>) {
+        diags.into_iter().for_each(|diag| self.push(diag));
+    }
 }
 
 impl DefCollector<'_> {
@@ -873,12 +988,36 @@ impl DefCollector<'_> {
         resolved_res.resolved_def.take_macros()
     }
 
+    /// Returns the index of the first segment of the derive macro path `path` that doesn't resolve,
+    /// or `None` if the whole path resolves.
+    fn first_unresolved_segment(&self, module: LocalModuleId, path: &ModPath) -> Option<usize> {
+        let segments = path.segments();
+        for len in 1..segments.len() {
+            let prefix = ModPath::from_segments(path.kind.clone(), segments[..len].iter().cloned());
+            let resolved_res = self.def_map.resolve_path_fp_with_macro(
+                self.db,
+                ResolveMode::Other,
+                module,
+                &prefix,
+                BuiltinShadowMode::Module,
+            );
+            if resolved_res.resolved_def.take_types().is_none() {
+                return Some(len - 1);
+            }
+        }
+        match self.resolve_derive_macro(module, path) {
+            Some(_) => None,
+            None => Some(segments.len() - 1),
+        }
+    }
+
     fn collect_macro_expansion(
         &mut self,
         module_id: LocalModuleId,
         macro_call_id: MacroCallId,
         depth: usize,
     ) {
+        self.def_map.modules[module_id].scope.add_macro_invocation(macro_call_id);
         if depth > EXPANSION_DEPTH_LIMIT {
             cov_mark::hit!(macro_expansion_overflow);
             log::warn!("macro expansion is too deep");
@@ -899,10 +1038,13 @@ impl DefCollector<'_> {
                         // Missing proc macros are non-fatal, so they are handled specially.
                         DefDiagnostic::unresolved_proc_macro(module_id, loc.kind)
                     }
-                    _ => DefDiagnostic::macro_error(module_id, loc.kind, err.to_string()),
+                    hir_expand::ExpandError::TokenLimitExceeded { token_count, .. } => {
+                        DefDiagnostic::macro_expansion_too_large(module_id, loc.kind, token_count)
+                    }
+                    _ => DefDiagnostic::macro_error(module_id, loc.kind, err),
                 };
 
-                self.def_map.diagnostics.push(diag);
+                self.diagnostics.push(diag);
             }
             // FIXME: Handle eager macros.
         }
@@ -921,6 +1063,20 @@ impl DefCollector<'_> {
         .collect(item_tree.top_level_items());
     }
 
+    /// Whether `path` starts with more `super`s than there are modules above `module`.
+    ///
+    /// In block `DefMap`s, extra `super`s continue in the containing module, so they are not
+    /// considered here.
+    fn super_beyond_root(&self, module: LocalModuleId, path: &ModPath) -> bool {
+        let lvl = match path.kind {
+            PathKind::Super(lvl) if self.def_map.block.is_none() => lvl,
+            _ => return false,
+        };
+        let depth =
+            iter::successors(self.def_map[module].parent, |&it| self.def_map[it].parent).count();
+        lvl as usize > depth
+    }
+
     fn finish(mut self) -> DefMap {
         // Emit diagnostics for all remaining unexpanded macros.
 
@@ -944,15 +1100,50 @@ impl DefCollector<'_> {
                 ) {
                     Ok(_) => (),
                     Err(UnresolvedMacro { path }) => {
-                        self.def_map.diagnostics.push(DefDiagnostic::unresolved_macro_call(
+                        // A `macro_rules!` macro that is in textual scope by the end of the
+                        // module, but was not when the call was collected, is defined after it.
+                        let defined_later = match (&path.kind, path.segments()) {
+                            (PathKind::Super(0), [name]) => self.def_map[directive.module_id]
+                                .scope
+                                .get_legacy_macro(name)
+                                .and_then(|it| match it.kind {
+                                    MacroDefKind::Declarative(def) => Some(def),
+                                    _ => None,
+                                }),
+                            _ => None,
+                        };
+                        let diagnostic = match defined_later {
+                            Some(def) => DefDiagnostic::macro_used_before_definition(
+                                directive.module_id,
+                                ast_id.ast_id,
+                                def,
+                            ),
+                            None => DefDiagnostic::unresolved_macro_call(
+                                directive.module_id,
+                                ast_id.ast_id,
+                                path,
+                            ),
+                        };
+                        self.diagnostics.push(diagnostic);
+                    }
+                },
+                MacroDirectiveKind::Derive { ast_id, .. } => {
+                    // Unqualified derives are usually builtin or come from the prelude, so
+                    // reporting them when the standard library isn't available would be noisy.
+                    // FIXME: we might want to diagnose them too
+                    if ast_id.path.segments().len() < 2 {
+                        continue;
+                    }
+                    if let Some(failed_segment) =
+                        self.first_unresolved_segment(directive.module_id, &ast_id.path)
+                    {
+                        self.diagnostics.push(DefDiagnostic::unresolved_derive_path(
                             directive.module_id,
                             ast_id.ast_id,
-                            path,
+                            ast_id.path.clone(),
+                            failed_segment,
                         ));
                     }
-                },
-                MacroDirectiveKind::Derive { .. } => {
-                    // FIXME: we might want to diagnose this too
                 }
             }
         }
@@ -972,7 +1163,7 @@ impl DefCollector<'_> {
 
                 diagnosed_extern_crates.insert(extern_crate.name.clone());
 
-                self.def_map.diagnostics.push(DefDiagnostic::unresolved_extern_crate(
+                self.diagnostics.push(DefDiagnostic::unresolved_extern_crate(
                     directive.module_id,
                     InFile::new(krate.file_id(), extern_crate.ast_id),
                 ));
@@ -993,7 +1184,44 @@ impl DefCollector<'_> {
                     _ => {}
                 }
 
-                self.def_map.diagnostics.push(DefDiagnostic::unresolved_import(
+                if self.super_beyond_root(directive.module_id, &directive.import.path) {
+                    self.diagnostics.push(DefDiagnostic::super_beyond_root(
+                        directive.module_id,
+                        *import,
+                        import_data.index,
+                    ));
+                    continue;
+                }
+
+                // `use self::X;` refers to itself if the only other items called `X` in the module
+                // are unresolved imports, like `use X;`. Without those, `X` is simply missing.
+                let refers_to_itself = directive.import.is_self_referential()
+                    && self.unresolved_imports.iter().any(|other| {
+                        other.module_id == directive.module_id
+                            && other.import.source != directive.import.source
+                            && other.import.defined_name() == directive.import.defined_name()
+                    });
+                if refers_to_itself {
+                    self.diagnostics.push(DefDiagnostic::self_referential_import(
+                        directive.module_id,
+                        *import,
+                        import_data.index,
+                    ));
+                    continue;
+                }
+
+                // `use Trait as _;` can only be useful for traits, so the missing item is reported
+                // as such.
+                if import_data.alias == Some(ImportAlias::Underscore) {
+                    self.diagnostics.push(DefDiagnostic::unresolved_trait_import(
+                        directive.module_id,
+                        *import,
+                        import_data.index,
+                    ));
+                    continue;
+                }
+
+                self.diagnostics.push(DefDiagnostic::unresolved_import(
                     directive.module_id,
                     InFile::new(import.file_id(), import_data.ast_id),
                     import_data.index,
@@ -1001,8 +1229,329 @@ impl DefCollector<'_> {
             }
         }
 
+        if self.diagnostics.reports(Severity::Warning) {
+            self.emit_reexport_of_private_module_item_diagnostics();
+            self.emit_crate_self_alias_diagnostics();
+            self.emit_glob_of_self_diagnostics();
+            self.emit_unknown_tool_lint_diagnostics();
+        }
+        if self.diagnostics.reports(Severity::WeakWarning) {
+            self.emit_glob_overrides_prelude_diagnostics();
+            self.emit_redundant_extern_crate_core_diagnostics();
+        }
+        self.emit_overlapping_impl_diagnostics();
+
+        self.def_map.diagnostics = self.diagnostics.stored.into_vec();
         self.def_map
     }
+
+    /// Reports trait impls that are declared more than once in the same module with exactly the
+    /// same header, like `impl Foo for S {}` written twice. Impls that only overlap for some types
+    /// are left to the coherence check during type inference.
+    fn emit_overlapping_impl_diagnostics(&mut self) {
+        let mut diagnostics = Vec::new();
+        for (module_id, module) in self.def_map.modules() {
+            let mut seen = FxHashMap::default();
+            for impl_id in module.scope.impls() {
+                let loc = self.db.lookup_intern_impl(impl_id);
+                let item_tree = loc.id.item_tree(self.db);
+                let imp = &item_tree[loc.id.value];
+                let target_trait = match &imp.target_trait {
+                    Some(it) => it.clone(),
+                    // Inherent impls may be split up arbitrarily.
+                    None => continue,
+                };
+                let header = (
+                    target_trait,
+                    imp.self_ty.clone(),
+                    imp.generic_params.clone(),
+                    imp.is_negative,
+                );
+                let ast_id = InFile::new(loc.id.file_id(), imp.ast_id);
+                match seen.entry(header) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(ast_id);
+                    }
+                    Entry::Occupied(entry) => diagnostics.push(DefDiagnostic::overlapping_impl(
+                        module_id,
+                        *entry.get(),
+                        ast_id,
+                    )),
+                }
+            }
+        }
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Reports `extern crate core;` in crates that link `std`, where the items of `core` are
+    /// available through `std`. Renaming declarations are left alone, and `extern crate alloc;` is
+    /// needed to use `alloc` at all.
+    fn emit_redundant_extern_crate_core_diagnostics(&mut self) {
+        if !self.def_map.extern_prelude.contains_key(&name![std]) {
+            return;
+        }
+        let file_id = self.db.crate_graph()[self.def_map.krate].root_file_id;
+        let crate_attrs =
+            self.db.file_item_tree(file_id.into()).top_level_attrs(self.db, self.def_map.krate);
+        if crate_attrs.by_key("no_std").exists() || crate_attrs.by_key("no_core").exists() {
+            return;
+        }
+
+        let mut diagnostics = Vec::new();
+        for directive in &self.resolved_imports {
+            let krate = match directive.import.source {
+                ImportSource::ExternCrate(krate) => krate,
+                ImportSource::Import(_) => continue,
+            };
+            let item_tree = krate.item_tree(self.db);
+            let extern_crate = &item_tree[krate.value];
+            if extern_crate.alias.is_some() || extern_crate.name != name![core] {
+                continue;
+            }
+            diagnostics.push(DefDiagnostic::redundant_extern_crate_core(
+                directive.module_id,
+                InFile::new(krate.file_id(), extern_crate.ast_id),
+                extern_crate.name.to_string(),
+            ));
+        }
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Reports tool attributes like `#[unknown_tool::lint]` whose tool is neither built in nor
+    /// registered with `#![register_tool]`, and doesn't resolve to a module.
+    fn emit_unknown_tool_lint_diagnostics(&mut self) {
+        if self.tool_attrs.is_empty() {
+            return;
+        }
+        let file_id = self.db.crate_graph()[self.def_map.krate].root_file_id;
+        let crate_attrs =
+            self.db.file_item_tree(file_id.into()).top_level_attrs(self.db, self.def_map.krate);
+        let registered_tools: FxHashSet<_> = crate_attrs
+            .by_key("register_tool")
+            .tt_values()
+            .flat_map(|tt| tt.token_trees.iter())
+            .filter_map(|tt| match tt {
+                tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) => Some(ident.text.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let mut diagnostics = Vec::new();
+        for (module_id, ast_id, tool) in &self.tool_attrs {
+            if registered_tools.contains(tool.to_string().as_str()) {
+                continue;
+            }
+            let path = ModPath::from_segments(PathKind::Plain, iter::once(tool.clone()));
+            let resolved_res = self.def_map.resolve_path_fp_with_macro(
+                self.db,
+                ResolveMode::Other,
+                *module_id,
+                &path,
+                BuiltinShadowMode::Module,
+            );
+            if resolved_res.resolved_def.take_types().is_some() {
+                continue;
+            }
+            diagnostics.push(DefDiagnostic::unknown_tool_lint(
+                *module_id,
+                *ast_id,
+                tool.to_string(),
+            ));
+        }
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Reports glob imports from the module they are in, like `use self::*;`.
+    fn emit_glob_of_self_diagnostics(&mut self) {
+        let mut seen = FxHashSet::default();
+        let mut diagnostics = Vec::new();
+        for directive in &self.resolved_imports {
+            if !directive.import.is_glob {
+                continue;
+            }
+            let import = match directive.import.source {
+                ImportSource::Import(import) => import,
+                ImportSource::ExternCrate(_) => continue,
+            };
+            let module = self.def_map.module_id(directive.module_id);
+            if directive.status.namespaces().take_types() != Some(ModuleDefId::ModuleId(module))
+                || !seen.insert(import)
+            {
+                continue;
+            }
+
+            let index = import.item_tree(self.db)[import.value].index;
+            diagnostics.push(DefDiagnostic::glob_of_self(directive.module_id, import, index));
+        }
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Reports `use crate as name;` imports where `name` is also the name of a dependency. Paths
+    /// starting with `name` then refer to this crate instead of the dependency.
+    fn emit_crate_self_alias_diagnostics(&mut self) {
+        let mut seen = FxHashSet::default();
+        let mut diagnostics = Vec::new();
+        for directive in &self.resolved_imports {
+            let import = match directive.import.source {
+                ImportSource::Import(import) => import,
+                ImportSource::ExternCrate(_) => continue,
+            };
+            let path = &directive.import.path;
+            if path.kind != PathKind::Crate || !path.segments().is_empty() {
+                continue;
+            }
+            let name = match &directive.import.alias {
+                Some(ImportAlias::Alias(name)) => name,
+                _ => continue,
+            };
+            if self.def_map.resolve_name_in_extern_prelude(self.db, name).is_none()
+                || !seen.insert(import)
+            {
+                continue;
+            }
+
+            let index = import.item_tree(self.db)[import.value].index;
+            diagnostics.push(DefDiagnostic::crate_self_alias(
+                directive.module_id,
+                import,
+                index,
+                name.to_string(),
+            ));
+        }
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Reports `pub use` items that re-export an item which is private to its module or only
+    /// visible within the crate, like `pub(crate)` ones. rustc rejects these re-exports, while
+    /// re-exporting a `pub` item of a private module is fine.
+    fn emit_reexport_of_private_module_item_diagnostics(&mut self) {
+        let mut seen = FxHashSet::default();
+        let mut diagnostics = Vec::new();
+        for directive in &self.resolved_imports {
+            if directive.import.is_glob || directive.import.visibility != RawVisibility::Public {
+                continue;
+            }
+            let import = match directive.import.source {
+                ImportSource::Import(import) => import,
+                ImportSource::ExternCrate(_) => continue,
+            };
+            if !seen.insert(import) {
+                continue;
+            }
+
+            let path = &directive.import.path;
+            let (name, prefix) = match path.segments().split_last() {
+                Some((name, prefix)) if !prefix.is_empty() => (name, prefix),
+                _ => continue,
+            };
+            let prefix = ModPath::from_segments(path.kind.clone(), prefix.iter().cloned());
+            let (res, _) = self.def_map.resolve_path(
+                self.db,
+                directive.module_id,
+                &prefix,
+                BuiltinShadowMode::Module,
+            );
+            let module = match res.take_types() {
+                Some(ModuleDefId::ModuleId(it)) => it,
+                _ => continue,
+            };
+            if module.krate != self.def_map.krate || module.block != self.def_map.block_id() {
+                continue;
+            }
+
+            let item = self.def_map[module.local_id].scope.get(name);
+            let visibilities: Vec<Visibility> = [item.types, item.values]
+                .iter()
+                .flatten()
+                .map(|(_, vis)| *vis)
+                .chain(item.macros.iter().map(|(_, vis)| *vis))
+                .collect();
+            if visibilities.is_empty() || visibilities.contains(&Visibility::Public) {
+                continue;
+            }
+
+            let index = import.item_tree(self.db)[import.value].index;
+            diagnostics.push(DefDiagnostic::reexport_of_private_module_item(
+                directive.module_id,
+                import,
+                index,
+                module,
+            ));
+        }
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Reports names brought into scope by a glob import that the prelude provides as well. The
+    /// glob import takes precedence, which might not be what the user expects.
+    fn emit_glob_overrides_prelude_diagnostics(&mut self) {
+        let prelude = match self.def_map.prelude {
+            Some(it) => it,
+            None => return,
+        };
+        let prelude_def_map;
+        let prelude_scope =
+            if prelude.krate == self.def_map.krate && prelude.block == self.def_map.block_id() {
+                &self.def_map[prelude.local_id].scope
+            } else {
+                prelude_def_map = prelude.def_map(self.db);
+                &prelude_def_map[prelude.local_id].scope
+            };
+
+        let mut seen = FxHashSet::default();
+        let mut diagnostics = Vec::new();
+        for directive in &self.resolved_imports {
+            if !directive.import.is_glob || directive.import.is_prelude {
+                continue;
+            }
+            let import = match directive.import.source {
+                ImportSource::Import(import) => import,
+                ImportSource::ExternCrate(_) => continue,
+            };
+            if !seen.insert(import) {
+                continue;
+            }
+            let m = match directive.status.namespaces().take_types() {
+                Some(ModuleDefId::ModuleId(m)) => m,
+                _ => continue,
+            };
+            if m == prelude {
+                continue;
+            }
+
+            let source_def_map;
+            let source_scope =
+                if m.krate == self.def_map.krate && m.block == self.def_map.block_id() {
+                    &self.def_map[m.local_id].scope
+                } else {
+                    source_def_map = m.def_map(self.db);
+                    &source_def_map[m.local_id].scope
+                };
+            let scope = &self.def_map[directive.module_id].scope;
+
+            let index = import.item_tree(self.db)[import.value].index;
+            for (name, res) in source_scope.entries() {
+                if prelude_scope.get(name).is_none() {
+                    continue;
+                }
+                // Only report the name if the glob import is what actually brought it into scope.
+                let from_glob = (res.types.is_some()
+                    && scope.resolve_name(name, Namespace::Types) == res.take_types())
+                    || (res.values.is_some()
+                        && scope.resolve_name(name, Namespace::Values) == res.take_values())
+                    || (res.macros.is_some() && scope.get(name).take_macros() == res.take_macros());
+                if !from_glob {
+                    continue;
+                }
+                diagnostics.push(DefDiagnostic::glob_overrides_prelude(
+                    directive.module_id,
+                    import,
+                    index,
+                    name.to_string(),
+                ));
+            }
+        }
+        self.diagnostics.extend(diagnostics);
+    }
 }
 
 /// Walks a single module, populating defs, imports and macros
@@ -1053,18 +1602,30 @@ impl ModCollector<'_, '_> {
 
         for &item in items {
             let attrs = self.item_tree.attrs(self.def_collector.db, krate, item.into());
+            self.emit_contradictory_cfg_diagnostic(item, &attrs);
             if let Some(cfg) = attrs.cfg() {
                 if !self.is_cfg_enabled(&cfg) {
                     self.emit_unconfigured_diagnostic(item, &cfg);
                     continue;
                 }
             }
+            self.emit_deprecated_without_note_diagnostic(item, &attrs);
+            self.record_lang_item(item, &attrs);
+            self.record_tool_attrs(item, &attrs);
             let module = self.def_collector.def_map.module_id(self.module_id);
 
             let mut def = None;
             match item {
                 ModItem::Mod(m) => self.collect_module(&self.item_tree[m], &attrs),
                 ModItem::Import(import_id) => {
+                    let import = &self.item_tree[import_id];
+                    if import.is_bad_self {
+                        self.def_collector.diagnostics.push(DefDiagnostic::bad_self_import(
+                            self.module_id,
+                            ItemTreeId::new(self.file_id, import_id),
+                            import.index,
+                        ));
+                    }
                     self.def_collector.unresolved_imports.push(ImportDirective {
                         module_id: self.module_id,
                         import: Import::from_use(
@@ -1088,7 +1649,20 @@ impl ModCollector<'_, '_> {
                         status: PartialResolvedImport::Unresolved,
                     })
                 }
-                ModItem::MacroCall(mac) => self.collect_macro_call(&self.item_tree[mac]),
+                ModItem::MacroCall(mac) => {
+                    let mac = &self.item_tree[mac];
+                    if mac.in_extern_block {
+                        self.def_collector.diagnostics.push(
+                            DefDiagnostic::unsupported_macro_position(
+                                self.module_id,
+                                InFile::new(self.file_id, mac.ast_id),
+                                "an `extern` block".to_string(),
+                            ),
+                        );
+                    } else {
+                        self.collect_macro_call(mac)
+                    }
+                }
                 ModItem::MacroRules(id) => self.collect_macro_rules(id),
                 ModItem::MacroDef(id) => self.collect_macro_def(id),
                 ModItem::Impl(imp) => {
@@ -1103,6 +1677,10 @@ impl ModCollector<'_, '_> {
 
                     let ast_id = InFile::new(self.file_id, func.ast_id);
                     self.collect_proc_macro_def(&func.name, ast_id, &attrs);
+                    if func.flags.bits & FnFlags::IS_IN_EXTERN_BLOCK == 0 {
+                        self.record_exported_symbol(item, &attrs, &func.name);
+                    }
+                    self.emit_nested_impl_trait_diagnostic(id);
 
                     def = Some(DefData {
                         id: FunctionLoc {
@@ -1123,6 +1701,8 @@ impl ModCollector<'_, '_> {
                     // in which case we don't add the invocation, just a single attribute
                     // macro invocation
                     self.collect_derives(&attrs, it.ast_id.upcast());
+                    self.emit_invalid_repr_diagnostics(item, &attrs);
+                    self.emit_helper_attr_without_derive_diagnostics(item, &attrs);
 
                     def = Some(DefData {
                         id: StructLoc { container: module, id: ItemTreeId::new(self.file_id, id) }
@@ -1140,6 +1720,8 @@ impl ModCollector<'_, '_> {
                     // in which case we don't add the invocation, just a single attribute
                     // macro invocation
                     self.collect_derives(&attrs, it.ast_id.upcast());
+                    self.emit_invalid_repr_diagnostics(item, &attrs);
+                    self.emit_helper_attr_without_derive_diagnostics(item, &attrs);
 
                     def = Some(DefData {
                         id: UnionLoc { container: module, id: ItemTreeId::new(self.file_id, id) }
@@ -1157,6 +1739,14 @@ impl ModCollector<'_, '_> {
                     // in which case we don't add the invocation, just a single attribute
                     // macro invocation
                     self.collect_derives(&attrs, it.ast_id.upcast());
+                    self.emit_invalid_repr_diagnostics(item, &attrs);
+                    self.emit_helper_attr_without_derive_diagnostics(item, &attrs);
+                    if it.variants.is_empty() {
+                        self.def_collector.diagnostics.push(DefDiagnostic::empty_enum(
+                            self.module_id,
+                            InFile::new(self.file_id, it.ast_id),
+                        ));
+                    }
 
                     def = Some(DefData {
                         id: EnumLoc { container: module, id: ItemTreeId::new(self.file_id, id) }
@@ -1195,6 +1785,10 @@ impl ModCollector<'_, '_> {
                 ModItem::Static(id) => {
                     let it = &self.item_tree[id];
 
+                    if !it.is_extern {
+                        self.record_exported_symbol(item, &attrs, &it.name);
+                    }
+
                     def = Some(DefData {
                         id: StaticLoc { container: module, id: ItemTreeId::new(self.file_id, id) }
                             .intern(self.def_collector.db)
@@ -1206,6 +1800,17 @@ impl ModCollector<'_, '_> {
                 }
                 ModItem::Trait(id) => {
                     let it = &self.item_tree[id];
+                    for &item in it.items_with_visibility.iter() {
+                        self.def_collector.diagnostics.push(
+                            DefDiagnostic::redundant_visibility_on_trait_item(
+                                self.module_id,
+                                InFile::new(
+                                    self.file_id,
+                                    ModItem::from(item).ast_id(self.item_tree),
+                                ),
+                            ),
+                        );
+                    }
 
                     def = Some(DefData {
                         id: TraitLoc { container: module, id: ItemTreeId::new(self.file_id, id) }
@@ -1234,6 +1839,8 @@ impl ModCollector<'_, '_> {
             }
 
             if let Some(DefData { id, name, visibility, has_constructor }) = def {
+                self.emit_prelude_shadow_diagnostic(item, name);
+                self.emit_non_ascii_identifier_diagnostic(item, name);
                 self.def_collector.def_map.modules[self.module_id].scope.define_def(id);
                 let vis = self
                     .def_collector
@@ -1253,6 +1860,9 @@ impl ModCollector<'_, '_> {
     fn collect_module(&mut self, module: &Mod, attrs: &Attrs) {
         let path_attr = attrs.by_key("path").string_value();
         let is_macro_use = attrs.by_key("macro_use").exists();
+        if let Some(path) = path_attr {
+            self.emit_non_portable_path_diagnostic(module, path);
+        }
         match &module.kind {
             // inline module, just recurse
             ModKind::Inline { items } => {
@@ -1263,6 +1873,18 @@ impl ModCollector<'_, '_> {
                     &self.item_tree[module.visibility],
                 );
 
+                if path_attr.is_none() {
+                    let db = self.def_collector.db;
+                    if let Some(file) = self.mod_dir.file_for_inline(db, self.file_id, &module.name)
+                    {
+                        self.def_collector.diagnostics.push(DefDiagnostic::module_inline_and_file(
+                            self.module_id,
+                            AstId::new(self.file_id, module.ast_id),
+                            file,
+                        ));
+                    }
+                }
+
                 if let Some(mod_dir) = self.mod_dir.descend_into_definition(&module.name, path_attr)
                 {
                     ModCollector {
@@ -1319,9 +1941,11 @@ impl ModCollector<'_, '_> {
                         }
                     }
                     Err(candidate) => {
-                        self.def_collector.def_map.diagnostics.push(
-                            DefDiagnostic::unresolved_module(self.module_id, ast_id, candidate),
-                        );
+                        self.def_collector.diagnostics.push(DefDiagnostic::unresolved_module(
+                            self.module_id,
+                            ast_id,
+                            candidate,
+                        ));
                     }
                 };
             }
@@ -1403,6 +2027,11 @@ impl ModCollector<'_, '_> {
         let export_attr = attrs.by_key("macro_export");
 
         let is_export = export_attr.exists();
+        if is_export && self.def_collector.def_map.block_id().is_some() {
+            self.def_collector
+                .diagnostics
+                .push(DefDiagnostic::ineffective_macro_export(self.module_id, ast_id));
+        }
         let is_local_inner = if is_export {
             export_attr.tt_values().map(|it| &it.token_trees).flatten().any(|it| match it {
                 tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) => {
@@ -1439,6 +2068,19 @@ impl ModCollector<'_, '_> {
         }
 
         // Case 2: normal `macro_rules!` macro
+        // Macros that expand to a `macro_rules!` can define it again each time they're called, so
+        // only macros written out in the source are checked.
+        if self.macro_depth == 0 {
+            let key = (self.module_id, mac.name.clone());
+            if let Some(first) = self.def_collector.macro_rules_defs.insert(key, ast_id) {
+                self.def_collector.diagnostics.push(DefDiagnostic::duplicate_macro_definition(
+                    self.module_id,
+                    first,
+                    ast_id,
+                    mac.name.to_string(),
+                ));
+            }
+        }
         let macro_id = MacroDefId {
             krate: self.def_collector.def_map.krate,
             kind: MacroDefKind::Declarative(ast_id),
@@ -1524,10 +2166,10 @@ impl ModCollector<'_, '_> {
             }
             Ok(Err(_)) => {
                 // Built-in macro failed eager expansion.
-                self.def_collector.def_map.diagnostics.push(DefDiagnostic::macro_error(
+                self.def_collector.diagnostics.push(DefDiagnostic::macro_error(
                     self.module_id,
                     MacroCallKind::FnLike { ast_id: ast_id.ast_id },
-                    error.unwrap().to_string(),
+                    error.unwrap(),
                 ));
                 return;
             }
@@ -1554,15 +2196,281 @@ impl ModCollector<'_, '_> {
         }
     }
 
+    /// Reports `#[path]` attributes with backslashes when not compiling for Windows, which is the
+    /// only platform that treats them as path separators.
+    fn emit_non_portable_path_diagnostic(&mut self, module: &Mod, path: &str) {
+        if !path.contains('\\') {
+            return;
+        }
+        let windows = CfgExpr::Atom(CfgAtom::Flag("windows".into()));
+        if self.def_collector.cfg_options.check(&windows) == Some(true) {
+            return;
+        }
+        self.def_collector.diagnostics.push(DefDiagnostic::non_portable_path(
+            self.module_id,
+            AstId::new(self.file_id, module.ast_id),
+            path.to_string(),
+        ));
+    }
+
     fn is_cfg_enabled(&self, cfg: &CfgExpr) -> bool {
         self.def_collector.cfg_options.check(cfg) != Some(false)
     }
 
+    fn emit_prelude_shadow_diagnostic(&mut self, item: ModItem, name: &Name) {
+        // Aliases like `type Result<T> = std::result::Result<T, Error>;` are the usual way to give
+        // a module its own flavor of a prelude type.
+        if let ModItem::TypeAlias(_) = item {
+            return;
+        }
+        let prelude = match self.def_collector.def_map.prelude {
+            // A prelude from the crate being collected can't be inspected without a query cycle.
+            Some(it) if it.krate != self.def_collector.def_map.krate => it,
+            _ => return,
+        };
+        let prelude_def_map = prelude.def_map(self.def_collector.db);
+        if prelude_def_map[prelude.local_id].scope.get(name).is_none() {
+            return;
+        }
+
+        let ast_id = InFile::new(self.file_id, item.ast_id(self.item_tree));
+        self.def_collector.diagnostics.push(DefDiagnostic::prelude_shadow(
+            self.module_id,
+            ast_id,
+            name.to_string(),
+        ));
+    }
+
+    /// Reports helper attributes of well-known derive macros on ADTs that don't derive any of them,
+    /// like `#[serde(rename = "x")]` without `#[derive(Serialize)]`.
+    fn emit_helper_attr_without_derive_diagnostics(&mut self, item: ModItem, attrs: &Attrs) {
+        const KNOWN_HELPER_ATTRS: &[(&str, &[&str])] = &[("serde", &["Serialize", "Deserialize"])];
+
+        let db = self.def_collector.db;
+        let krate = self.def_collector.def_map.krate;
+        let item_tree = self.item_tree;
+        let fields_attrs = |fields: &Fields| match fields {
+            Fields::Record(range) | Fields::Tuple(range) => range
+                .clone()
+                .map(|field| item_tree.attrs(db, krate, field.into()))
+                .collect::<Vec<_>>(),
+            Fields::Unit => Vec::new(),
+        };
+        let mut all_attrs = vec![attrs.clone()];
+        match item {
+            ModItem::Struct(id) => all_attrs.extend(fields_attrs(&item_tree[id].fields)),
+            ModItem::Union(id) => all_attrs.extend(fields_attrs(&item_tree[id].fields)),
+            ModItem::Enum(id) => {
+                for variant in item_tree[id].variants.clone() {
+                    all_attrs.push(item_tree.attrs(db, krate, variant.into()));
+                    all_attrs.extend(fields_attrs(&item_tree[variant].fields));
+                }
+            }
+            _ => return,
+        }
+
+        let derived: Vec<String> = attrs
+            .by_key("derive")
+            .attrs()
+            .filter_map(|derive| derive.parse_derive())
+            .flatten()
+            .filter_map(|path| path.segments().last().map(|name| name.to_string()))
+            .collect();
+        for (helper, derives) in KNOWN_HELPER_ATTRS {
+            let used = all_attrs.iter().any(|attrs| attrs.by_key(helper).exists());
+            if used && !derives.iter().any(|derive| derived.iter().any(|it| it == derive)) {
+                self.def_collector.diagnostics.push(DefDiagnostic::helper_attr_without_derive(
+                    self.module_id,
+                    InFile::new(self.file_id, item.ast_id(self.item_tree)),
+                    helper.to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Reports `repr` hints that aren't known to rustc, like `#[repr(banana)]`.
+    fn emit_invalid_repr_diagnostics(&mut self, item: ModItem, attrs: &Attrs) {
+        let mut invalid = Vec::new();
+        for tt in attrs.by_key("repr").tt_values() {
+            let hints = tt.token_trees.split(
+                |tt| matches!(tt, tt::TokenTree::Leaf(tt::Leaf::Punct(punct)) if punct.char == ','),
+            );
+            for hint in hints {
+                let name = match hint.first() {
+                    Some(tt::TokenTree::Leaf(tt::Leaf::Ident(ident))) => &ident.text,
+                    _ => continue,
+                };
+                let is_known = matches!(
+                    name.as_str(),
+                    "C" | "Rust" | "transparent" | "simd" | "packed" | "align"
+                ) || BuiltinInt::from_suffix(name).is_some()
+                    || BuiltinUint::from_suffix(name).is_some();
+                if !is_known {
+                    invalid.push(name.to_string());
+                }
+            }
+        }
+
+        let ast_id = InFile::new(self.file_id, item.ast_id(self.item_tree));
+        for value in invalid {
+            self.def_collector.diagnostics.push(DefDiagnostic::invalid_repr(
+                self.module_id,
+                ast_id,
+                value,
+            ));
+        }
+    }
+
+    /// Remembers the item claiming a `#[lang = "..."]` item, and reports it if an earlier item of
+    /// the crate already claimed it.
+    fn record_lang_item(&mut self, item: ModItem, attrs: &Attrs) {
+        let lang = match attrs.by_key("lang").string_value() {
+            Some(it) => it.to_string(),
+            None => return,
+        };
+        let ast_id = InFile::new(self.file_id, item.ast_id(self.item_tree));
+        match self.def_collector.lang_items.entry(lang.clone()) {
+            Entry::Vacant(entry) => {
+                entry.insert(ast_id);
+            }
+            Entry::Occupied(entry) => {
+                self.def_collector.diagnostics.push(DefDiagnostic::duplicate_lang_item(
+                    self.module_id,
+                    *entry.get(),
+                    ast_id,
+                    lang,
+                ));
+            }
+        }
+    }
+
+    /// Reports `#[deprecated]` attributes without a note, either as `#[deprecated = "note"]` or as
+    /// `#[deprecated(note = "note")]`.
+    fn emit_deprecated_without_note_diagnostic(&mut self, item: ModItem, attrs: &Attrs) {
+        let deprecated = attrs.by_key("deprecated");
+        if !deprecated.exists() || deprecated.string_value().is_some() {
+            return;
+        }
+        let has_note = deprecated.tt_values().any(|tt| {
+            tt.token_trees.iter().any(
+                |tt| matches!(tt, tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) if ident.text == "note"),
+            )
+        });
+        if has_note {
+            return;
+        }
+
+        let ast_id = InFile::new(self.file_id, item.ast_id(self.item_tree));
+        self.def_collector
+            .diagnostics
+            .push(DefDiagnostic::deprecated_without_note(self.module_id, ast_id));
+    }
+
+    /// Records the symbol exported by `#[no_mangle]` or `#[export_name]` on `item`, and reports
+    /// the attribute if another item already exports a symbol of that name. rustc accepts each of
+    /// them on its own, the clash only shows up at link time.
+    fn record_exported_symbol(&mut self, item: ModItem, attrs: &Attrs, name: &Name) {
+        let (attr, symbol) = match attrs.by_key("export_name").string_value() {
+            Some(it) => ("export_name", it.to_string()),
+            None if attrs.by_key("no_mangle").exists() => ("no_mangle", name.to_string()),
+            None => return,
+        };
+        let ast_id = InFile::new(self.file_id, item.ast_id(self.item_tree));
+        match self.def_collector.exported_symbols.entry(symbol) {
+            Entry::Vacant(entry) => {
+                entry.insert(ast_id);
+            }
+            Entry::Occupied(_) => {
+                self.def_collector.diagnostics.push(DefDiagnostic::duplicate_exported_symbol(
+                    self.module_id,
+                    ast_id,
+                    attr.to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Reports `impl Trait` types in the generic arguments of another `impl Trait` in the signature
+    /// of `func`, like `impl Into<impl Debug>`.
+    fn emit_nested_impl_trait_diagnostic(&mut self, func: FileItemTreeId<item_tree::Function>) {
+        let item_tree = self.item_tree;
+        let func = &item_tree[func];
+        let nested = func
+            .params
+            .clone()
+            .filter_map(|it| match &item_tree[it] {
+                Param::Normal(ty) => Some(&**ty),
+                Param::Varargs => None,
+            })
+            .chain(iter::once(&*func.ret_type))
+            .any(has_nested_impl_trait);
+        if nested {
+            self.def_collector.diagnostics.push(DefDiagnostic::disallowed_nested_impl_trait(
+                self.module_id,
+                InFile::new(self.file_id, func.ast_id),
+            ));
+        }
+    }
+
+    /// Records attributes with a path like `tool::attr` whose first segment isn't one of the tools
+    /// known to rustc.
+    fn record_tool_attrs(&mut self, item: ModItem, attrs: &Attrs) {
+        const KNOWN_TOOLS: &[&str] = &["clippy", "rustfmt", "rustdoc", "miri", "rust_analyzer"];
+
+        for attr in attrs.iter() {
+            let segments = attr.path.segments();
+            if attr.path.kind != PathKind::Plain || segments.len() < 2 {
+                continue;
+            }
+            let tool = &segments[0];
+            if KNOWN_TOOLS.contains(&tool.to_string().as_str()) {
+                continue;
+            }
+            let ast_id = InFile::new(self.file_id, item.ast_id(self.item_tree));
+            self.def_collector.tool_attrs.push((self.module_id, ast_id, tool.clone()));
+        }
+    }
+
+    fn emit_non_ascii_identifier_diagnostic(&mut self, item: ModItem, name: &Name) {
+        let name = name.to_string();
+        if name.is_ascii() {
+            return;
+        }
+
+        let ast_id = InFile::new(self.file_id, item.ast_id(self.item_tree));
+        self.def_collector.diagnostics.push(DefDiagnostic::non_ascii_identifier(
+            self.module_id,
+            ast_id,
+            name,
+        ));
+    }
+
+    /// Reports items with several `#[cfg]` attributes that can't all be enabled at once, like
+    /// `#[cfg(unix)] #[cfg(windows)]`.
+    fn emit_contradictory_cfg_diagnostic(&mut self, item: ModItem, attrs: &Attrs) {
+        let cfgs: Vec<_> = attrs.by_key("cfg").tt_values().map(CfgExpr::parse).collect();
+        if cfgs.len() < 2 {
+            return;
+        }
+        // A single cfg that can never be enabled, like `#[cfg(any())]`, is a deliberate way to
+        // disable an item, not a conflict between the attributes.
+        if cfgs.iter().any(|cfg| DnfExpr::new(cfg.clone()).is_unsatisfiable()) {
+            return;
+        }
+        if !DnfExpr::new(CfgExpr::All(cfgs)).is_unsatisfiable() {
+            return;
+        }
+        let ast_id = InFile::new(self.file_id, item.ast_id(self.item_tree));
+        self.def_collector
+            .diagnostics
+            .push(DefDiagnostic::contradictory_cfg(self.module_id, ast_id));
+    }
+
     fn emit_unconfigured_diagnostic(&mut self, item: ModItem, cfg: &CfgExpr) {
         let ast_id = item.ast_id(self.item_tree);
 
         let ast_id = InFile::new(self.file_id, ast_id);
-        self.def_collector.def_map.diagnostics.push(DefDiagnostic::unconfigured_code(
+        self.def_collector.diagnostics.push(DefDiagnostic::unconfigured_code(
             self.module_id,
             ast_id,
             cfg.clone(),
@@ -1571,6 +2479,31 @@ impl ModCollector<'_, '_> {
     }
 }
 
+/// Returns whether an `impl Trait` in `type_ref` has another `impl Trait` in its generic arguments.
+/// Associated type bindings may contain `impl Trait`.
+fn has_nested_impl_trait(type_ref: &TypeRef) -> bool {
+    let mut nested = false;
+    type_ref.walk(&mut |it| {
+        let bounds = match it {
+            TypeRef::ImplTrait(bounds) => bounds,
+            _ => return,
+        };
+        for bound in bounds {
+            let path = match bound {
+                TypeBound::Path(it) => it,
+                TypeBound::Lifetime(_) | TypeBound::Error => continue,
+            };
+            let args = path.segments().iter().filter_map(|it| it.args_and_bindings);
+            for arg in args.flat_map(|it| it.args.iter()) {
+                if let GenericArg::Type(ty) = arg {
+                    ty.walk(&mut |it| nested |= matches!(it, TypeRef::ImplTrait(_)));
+                }
+            }
+        }
+    });
+    nested
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{db::DefDatabase, test_db::TestDB};
@@ -1591,6 +2524,11 @@ mod tests {
             proc_macros: Default::default(),
             exports_proc_macros: false,
             from_glob_import: Default::default(),
+            tool_attrs: Vec::new(),
+            macro_rules_defs: FxHashMap::default(),
+            lang_items: FxHashMap::default(),
+            exported_symbols: FxHashMap::default(),
+            diagnostics: DiagnosticStream::default(),
         };
         collector.seed_with_top_level();
         collector.collect();
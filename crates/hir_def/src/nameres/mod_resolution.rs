@@ -63,22 +63,7 @@ impl ModDir {
         attr_path: Option<&SmolStr>,
     ) -> Result<(FileId, bool, ModDir), String> {
         let orig_file_id = file_id.original_file(db.upcast());
-
-        let mut candidate_files = Vec::new();
-        match attr_path {
-            Some(attr_path) => {
-                candidate_files.push(self.dir_path.join_attr(attr_path, self.root_non_dir_owner))
-            }
-            None => {
-                if file_id.is_include_macro(db.upcast()) {
-                    candidate_files.push(format!("{}.rs", name));
-                    candidate_files.push(format!("{}/mod.rs", name));
-                } else {
-                    candidate_files.push(format!("{}{}.rs", self.dir_path.0, name));
-                    candidate_files.push(format!("{}{}/mod.rs", self.dir_path.0, name));
-                }
-            }
-        };
+        let mut candidate_files = self.candidate_files(db, file_id, name, attr_path);
 
         for candidate in candidate_files.iter() {
             let path = AnchoredPath { anchor: orig_file_id, path: candidate.as_str() };
@@ -97,6 +82,43 @@ impl ModDir {
         }
         Err(candidate_files.remove(0))
     }
+
+    /// Returns the file that `mod name;` would resolve to, for an inline `mod name { ... }`
+    /// without a `#[path]` attribute.
+    pub(super) fn file_for_inline(
+        &self,
+        db: &dyn DefDatabase,
+        file_id: HirFileId,
+        name: &Name,
+    ) -> Option<String> {
+        let orig_file_id = file_id.original_file(db.upcast());
+        self.candidate_files(db, file_id, name, None).into_iter().find(|candidate| {
+            let path = AnchoredPath { anchor: orig_file_id, path: candidate.as_str() };
+            db.resolve_path(path).is_some()
+        })
+    }
+
+    fn candidate_files(
+        &self,
+        db: &dyn DefDatabase,
+        file_id: HirFileId,
+        name: &Name,
+        attr_path: Option<&SmolStr>,
+    ) -> Vec<String> {
+        match attr_path {
+            Some(attr_path) => vec![self.dir_path.join_attr(attr_path, self.root_non_dir_owner)],
+            None => {
+                if file_id.is_include_macro(db.upcast()) {
+                    vec![format!("{}.rs", name), format!("{}/mod.rs", name)]
+                } else {
+                    vec![
+                        format!("{}{}.rs", self.dir_path.0, name),
+                        format!("{}{}/mod.rs", self.dir_path.0, name),
+                    ]
+                }
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
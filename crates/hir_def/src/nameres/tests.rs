@@ -10,7 +10,9 @@ use std::sync::Arc;
 use base_db::{fixture::WithFixture, SourceDatabase};
 use expect_test::{expect, Expect};
 
-use crate::{db::DefDatabase, test_db::TestDB};
+use hir_expand::name::Name;
+
+use crate::{db::DefDatabase, item_scope::Namespace, test_db::TestDB, AdtId, ModuleDefId};
 
 use super::DefMap;
 
@@ -732,3 +734,118 @@ fn bar() {}
         "#]],
     );
 }
+
+#[test]
+fn resolve_name_by_namespace() {
+    let def_map = compute_crate_def_map(
+        r#"
+//- /lib.rs
+struct Foo {}
+fn Foo() {}
+"#,
+    );
+    let scope = &def_map[def_map.root()].scope;
+    let (name, _) = scope.entries().find(|(name, _)| name.to_string() == "Foo").unwrap();
+
+    assert!(matches!(
+        scope.resolve_name(name, Namespace::Types),
+        Some(ModuleDefId::AdtId(AdtId::StructId(_)))
+    ));
+    assert!(matches!(
+        scope.resolve_name(name, Namespace::Values),
+        Some(ModuleDefId::FunctionId(_))
+    ));
+    assert_eq!(scope.resolve_name(&Name::missing(), Namespace::Types), None);
+}
+
+#[test]
+fn reexports() {
+    let db = TestDB::with_files(
+        r#"
+//- /lib.rs crate:main deps:dep
+pub mod api {
+    pub use crate::inner::{make, Thing};
+    pub use dep::Dep as Renamed;
+    use crate::inner::Private;
+    pub struct Own;
+}
+mod inner {
+    pub struct Thing;
+    pub fn make() {}
+    pub struct Private;
+}
+//- /dep.rs crate:dep
+pub struct Dep;
+"#,
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+    let (_, &api) = def_map[def_map.root()]
+        .children
+        .iter()
+        .find(|(name, _)| name.to_string() == "api")
+        .unwrap();
+
+    let reexports = db.reexports(def_map.module_id(api));
+    let actual = reexports
+        .iter()
+        .map(|(name, def)| {
+            let kind = match def {
+                ModuleDefId::AdtId(AdtId::StructId(_)) => "struct",
+                ModuleDefId::FunctionId(_) => "fn",
+                _ => "other",
+            };
+            let krate = if def.module(&db).unwrap().krate == krate { "main" } else { "dep" };
+            format!("{}: {} in {}\n", name, kind, krate)
+        })
+        .collect::<String>();
+    expect![[r#"
+        Renamed: struct in dep
+        Thing: struct in main
+        make: fn in main
+    "#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn canonical_paths() {
+    let db = TestDB::with_files(
+        r#"
+//- /lib.rs
+mod a {
+    mod b {
+        struct Foo;
+        fn bar() {
+            struct Local;
+        }
+    }
+    pub use self::b::*;
+}
+fn Foo() {}
+struct Foo {}
+"#,
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let paths = db.canonical_paths(krate);
+    let mut actual = paths
+        .iter()
+        .map(|(path, def)| {
+            let kind = match def {
+                ModuleDefId::ModuleId(_) => "mod",
+                ModuleDefId::AdtId(AdtId::StructId(_)) => "struct",
+                ModuleDefId::FunctionId(_) => "fn",
+                _ => "other",
+            };
+            format!("{}: {}\n", path, kind)
+        })
+        .collect::<Vec<_>>();
+    actual.sort();
+    expect![[r#"
+        crate::Foo: struct
+        crate::a: mod
+        crate::a::b: mod
+        crate::a::b::Foo: struct
+        crate::a::b::bar: fn
+    "#]]
+    .assert_eq(&actual.concat());
+}
@@ -1,6 +1,19 @@
-use base_db::fixture::WithFixture;
+use base_db::{fixture::WithFixture, SourceDatabase, SourceDatabaseExt};
+use hir_expand::{db::AstDatabase, diagnostics::DiagnosticSinkBuilder, AttrId, MacroCallKind};
+use syntax::{
+    ast::{self, ModuleItemOwner},
+    AstNode, SyntaxNodePtr, TextRange,
+};
 
-use crate::test_db::TestDB;
+use crate::{
+    db::DefDatabase,
+    nameres::{
+        diagnostics::{group_by_module, macro_call_source, summarize},
+        AnyDiagnostic, DedupDiagnostics, DefMap, DiagnosticCategory, Severity,
+    },
+    test_db::TestDB,
+    AstId,
+};
 
 fn check_diagnostics(ra_fixture: &str) {
     let db: TestDB = TestDB::with_files(ra_fixture);
@@ -12,6 +25,11 @@ fn check_no_diagnostics(ra_fixture: &str) {
     db.check_no_diagnostics();
 }
 
+fn check_unused_imports(ra_fixture: &str) {
+    let db: TestDB = TestDB::with_files(ra_fixture);
+    db.check_diagnostics_with_unused_imports(true);
+}
+
 #[test]
 fn unresolved_import() {
     check_diagnostics(
@@ -25,6 +43,22 @@ fn unresolved_import() {
     );
 }
 
+#[test]
+fn unresolved_trait_import() {
+    check_diagnostics(
+        r"
+        use foo::MissingTrait as _;
+          //^^^^^^^^^^^^^^^^^^^^^^ unresolved trait `MissingTrait` in underscore import
+        use foo::{Trait as _, Other as _};
+                            //^^^^^^^^^^ unresolved trait `Other` in underscore import
+
+        mod foo {
+            pub trait Trait {}
+        }
+        ",
+    );
+}
+
 #[test]
 fn unresolved_import_in_use_tree() {
     // Only the relevant part of a nested `use` item should be highlighted.
@@ -65,6 +99,28 @@ fn unresolved_extern_crate() {
     );
 }
 
+#[test]
+fn redundant_extern_crate_core() {
+    check_diagnostics(
+        r"
+        //- /main.rs crate:main deps:alloc,core,std
+          extern crate core;
+        //^^^^^^^^^^^^^^^^^^ `extern crate core` is redundant, its items are available through `std`
+        extern crate core as renamed;
+        extern crate alloc;
+        extern crate std;
+
+        //- /lib.rs crate:no_std_lib deps:core
+        #![no_std]
+        extern crate core;
+
+        //- /alloc.rs crate:alloc
+        //- /core.rs crate:core
+        //- /std.rs crate:std
+        ",
+    );
+}
+
 #[test]
 fn extern_crate_self_as() {
     cov_mark::check!(extern_crate_self_as);
@@ -175,6 +231,38 @@ fn unresolved_legacy_scope_macro() {
     );
 }
 
+#[test]
+fn macro_used_before_definition() {
+    check_diagnostics(
+        r#"
+        //- /lib.rs
+          m!();
+        //^^^^^ macro `m!` is used before its definition; `macro_rules!` macros are only visible after they are defined
+
+          macro_rules! m { () => {} }
+
+          m!();
+        "#,
+    );
+}
+
+#[test]
+fn unsupported_macro_position() {
+    check_diagnostics(
+        r#"
+        //- /lib.rs
+          macro_rules! decl { () => { fn f(); } }
+
+          extern "C" {
+              decl!();
+            //^^^^^^^^ macro calls in an `extern` block are not expanded by rust-analyzer yet
+          }
+
+          decl!();
+        "#,
+    );
+}
+
 #[test]
 fn unresolved_module_scope_macro() {
     check_diagnostics(
@@ -237,3 +325,1062 @@ fn good_out_dir_diagnostic() {
         "#,
     );
 }
+
+#[test]
+fn prelude_shadow() {
+    check_diagnostics(
+        r#"
+        //- /main.rs crate:main deps:std
+        enum Result { Ok }
+           //^^^^^^ `Result` shadows an item from the prelude
+        struct Foo;
+
+        mod m {
+            fn Option() {}
+             //^^^^^^ `Option` shadows an item from the prelude
+        }
+
+        mod io {
+            pub struct Error;
+            pub type Result<T> = std::prelude::Result<T, Error>;
+        }
+
+        //- /std.rs crate:std
+        #[prelude_import]
+        use prelude::*;
+        pub mod prelude {
+            pub enum Result<T, E> { Ok(T), Err(E) }
+            pub enum Option { None }
+        }
+        "#,
+    );
+}
+
+#[test]
+fn group_diagnostics_by_module() {
+    let db: TestDB = TestDB::with_files(
+        r"
+        //- /lib.rs
+        use does_not_exist;
+
+        mod a {
+            use does_not_exist_either;
+            mod b;
+        }
+        ",
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+    let a = def_map[def_map.root()]
+        .children
+        .iter()
+        .find_map(|(name, &id)| if name.to_string() == "a" { Some(id) } else { None })
+        .unwrap();
+
+    let grouped = group_by_module(def_map.diagnostics.clone());
+    assert_eq!(grouped.len(), 2);
+    assert_eq!(grouped[&def_map.root()].len(), 1);
+    assert_eq!(grouped[&a].len(), 2);
+}
+
+#[test]
+fn dedup_diagnostics() {
+    let db: TestDB = TestDB::with_files(
+        r"
+        //- /lib.rs
+        use does_not_exist;
+        mod missing;
+        ",
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+    assert_eq!(def_map.diagnostics.len(), 2);
+
+    let mut dedup = DedupDiagnostics::default();
+    for diag in def_map.diagnostics.iter().chain(&def_map.diagnostics) {
+        dedup.push(diag.clone());
+    }
+    assert!(!dedup.push(def_map.diagnostics[0].clone()));
+    assert_eq!(dedup.into_vec(), def_map.diagnostics);
+}
+
+#[test]
+fn to_any_diagnostic() {
+    let (db, file_id) = TestDB::with_single_file("use does_not_exist;");
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+
+    let any = def_map.diagnostics[0].to_any_diagnostic(&db).unwrap();
+    assert_eq!(
+        any,
+        AnyDiagnostic {
+            code: "unresolved-import",
+            message: "unresolved import".to_string(),
+            severity: Severity::Error,
+            file: file_id.into(),
+            range: TextRange::new(4.into(), 18.into()),
+        }
+    );
+}
+
+#[test]
+fn diagnostic_codes() {
+    let db: TestDB = TestDB::with_files(
+        r#"
+        //- /lib.rs
+        use does_not_exist;
+
+        mod missing;
+
+        enum Never {}
+        "#,
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+
+    let mut codes: Vec<_> = def_map.diagnostics.iter().map(|diag| diag.code()).collect();
+    codes.sort_unstable();
+    assert_eq!(codes, ["empty-enum", "unresolved-import", "unresolved-module"]);
+    // The codes agree with the ones of the syntax-based diagnostics.
+    for diag in def_map.diagnostics.iter() {
+        assert_eq!(diag.to_any_diagnostic(&db).unwrap().code, diag.code());
+    }
+}
+
+#[test]
+fn primary_range() {
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+use does_not_exist;
+enum Never {}
+#[cfg(FALSE)]
+fn f() {}
+"#,
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+    let text = db.file_text(file_id);
+
+    let mut ranges: Vec<_> = def_map
+        .diagnostics()
+        .iter()
+        .map(|diag| {
+            let range = diag.primary_range(&db).unwrap();
+            assert_eq!(range.file_id, file_id.into());
+            (diag.code(), &text[range.value])
+        })
+        .collect();
+    ranges.sort_unstable();
+    assert_eq!(
+        ranges,
+        [
+            ("empty-enum", "Never"),
+            ("inactive-code", "#[cfg(FALSE)]\nfn f() {}"),
+            ("unresolved-import", "does_not_exist"),
+        ]
+    );
+}
+
+#[test]
+fn render_message() {
+    let db: TestDB = TestDB::with_files(
+        r#"
+        //- /lib.rs
+        use does_not_exist;
+
+        mod missing;
+
+        #[rustc_builtin_macro]
+        macro_rules! include { () => {} }
+        include!("doesntexist");
+        "#,
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+
+    let mut messages: Vec<_> =
+        def_map.diagnostics.iter().map(|diag| diag.render_message(&db).unwrap()).collect();
+    messages.sort();
+    assert_eq!(
+        messages,
+        ["failed to load file `doesntexist`", "unresolved import", "unresolved module"]
+    );
+}
+
+#[test]
+fn summarize_diagnostics() {
+    let db: TestDB = TestDB::with_files(
+        r"
+        //- /lib.rs
+        use does_not_exist;
+        use foo::Unused;
+        use foo::Used;
+
+        mod a {
+            use does_not_exist_either;
+            mod b;
+        }
+
+        mod foo {
+            pub struct Unused;
+            pub struct Used;
+        }
+
+        fn f(_: Used) {}
+        ",
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+
+    let summary = summarize(krate, &db);
+    assert_eq!(summary.len(), 2);
+    assert_eq!(summary["unresolved-import"], 2);
+    assert_eq!(summary["unresolved-module"], 1);
+}
+
+#[test]
+fn diagnostics_affecting_file() {
+    let (db, file_id) = TestDB::with_single_file(
+        r"
+        use does_not_exist;
+
+        mod a {
+            use does_not_exist_in_a;
+        }
+
+        mod b {
+            mod c {
+                use does_not_exist_in_c;
+            }
+        }
+        ",
+    );
+
+    let diagnostics = db.diagnostics_affecting_file(file_id);
+    assert_eq!(diagnostics.len(), 3);
+
+    let root = db.parse(file_id).syntax_node();
+    let mut sources = Vec::new();
+    let mut sink = DiagnosticSinkBuilder::new().build(|it| {
+        sources.push(it.display_source().value.to_node(&root).text().to_string());
+    });
+    for diag in diagnostics.iter() {
+        diag.add_to_sink(&db, &mut sink);
+    }
+    drop(sink);
+    sources.sort();
+    assert_eq!(sources, ["does_not_exist", "does_not_exist_in_a", "does_not_exist_in_c"]);
+}
+
+#[test]
+fn glob_overrides_prelude() {
+    check_diagnostics(
+        r#"
+        //- /main.rs crate:main deps:std
+        pub use foo::*;
+              //^^^^^^ glob import overrides `Vec` from the prelude
+        pub use bar::*;
+
+        mod foo {
+            pub struct Vec;
+                     //^^^ `Vec` shadows an item from the prelude
+            pub struct Foo;
+        }
+        mod bar {
+            pub struct Bar;
+        }
+
+        //- /std.rs crate:std
+        #[prelude_import]
+        use prelude::*;
+        pub mod prelude {
+            pub struct Vec;
+        }
+        "#,
+    );
+}
+
+#[test]
+fn non_ascii_identifier() {
+    check_diagnostics(
+        r#"
+        fn ascii() {}
+        fn привет() {}
+         //^^^^^^^^^^^^ identifier `привет` contains non-ASCII characters
+        "#,
+    );
+}
+
+#[test]
+fn macro_call_source_fn_like() {
+    let (db, file_id) = TestDB::with_single_file(
+        r"
+        macro_rules! m { () => {} }
+        m!();
+        ",
+    );
+    let call = db.parse(file_id).tree().syntax().descendants().find_map(ast::MacroCall::cast);
+    let call = call.unwrap();
+    let ast_id = AstId::new(file_id.into(), db.ast_id_map(file_id.into()).ast_id(&call));
+
+    let src = macro_call_source(&db, &MacroCallKind::FnLike { ast_id });
+    assert_eq!(src.file_id, file_id.into());
+    assert_eq!(src.value, SyntaxNodePtr::new(call.syntax()));
+}
+
+#[test]
+fn macro_call_source_derive() {
+    let (db, file_id) = TestDB::with_single_file(
+        r"
+        #[allow(unused)]
+        #[derive(Debug, Foo)]
+        struct S;
+        ",
+    );
+    let item = db.parse(file_id).tree().items().next().unwrap();
+    let ast_id = AstId::new(file_id.into(), db.ast_id_map(file_id.into()).ast_id(&item));
+    let kind =
+        MacroCallKind::Derive { ast_id, derive_name: "Foo".to_string(), derive_attr: AttrId(0) };
+
+    let src = macro_call_source(&db, &kind);
+    assert_eq!(src.file_id, file_id.into());
+    assert_eq!(src.value, SyntaxNodePtr::new(item.syntax()));
+}
+
+#[test]
+fn bad_self_import() {
+    check_diagnostics(
+        r"
+        use foo::self;
+          //^^^^^^^^^ `self` imports are only allowed within a { } list
+        use foo::{self as bar};
+        use foo::{baz::self};
+                //^^^^^^^^^ `self` imports are only allowed within a { } list
+
+        fn f(_: foo::S, _: bar::S, _: baz::S) {}
+
+        mod foo {
+            pub struct S;
+            pub mod baz {
+                pub struct S;
+            }
+        }
+        ",
+    );
+}
+
+#[test]
+fn self_referential_import() {
+    check_diagnostics(
+        r"
+        //- /lib.rs
+        mod a {
+            use self::{X};
+                     //^ import of `X` refers to itself
+            use X;
+              //^ unresolved import
+        }
+        mod b {
+            use self::Z as Z;
+              //^^^^^^^^^^^^ import of `Z` refers to itself
+            use self::Y as Z;
+              //^^^^^^^^^^^^ unresolved import
+        }
+        mod d {
+            use self::Missing;
+              //^^^^^^^^^^^^^ unresolved import
+        }
+        mod c {
+            pub use self::S as T;
+            pub struct S;
+        }
+        ",
+    );
+}
+
+#[test]
+fn super_beyond_root() {
+    check_diagnostics(
+        r"
+        //- /lib.rs
+          use super::Foo;
+            //^^^^^^^^^^ there are too many leading `super` keywords
+        mod a {
+            use super::super::Bar;
+              //^^^^^^^^^^^^^^^^^ there are too many leading `super` keywords
+            pub use super::Baz;
+        }
+        pub struct Baz;
+        ",
+    );
+}
+
+#[test]
+fn module_inline_and_file() {
+    check_diagnostics(
+        r#"
+        //- /lib.rs
+          mod a {}
+        //^^^^^^^^ module is defined inline, so `a.rs` is ignored
+          mod b {
+              mod c {}
+            //^^^^^^^^ module is defined inline, so `b/c/mod.rs` is ignored
+          }
+          #[path = "d.rs"]
+          mod d {}
+          mod e {}
+        //- /a.rs
+        struct A;
+        //- /b/c/mod.rs
+        struct C;
+        //- /d.rs
+        struct D;
+        "#,
+    );
+}
+
+#[test]
+fn duplicate_macro_definition() {
+    check_diagnostics(
+        r#"
+        //- /lib.rs
+          macro_rules! m { () => {} }
+          macro_rules! m { () => {} }
+        //^^^^^^^^^^^^^^^^^^^^^^^^^^^ macro `m!` is defined multiple times in this module
+          mod inner {
+              macro_rules! m { () => {} }
+          }
+          macro_rules! define_helper { () => { macro_rules! helper { () => {} } } }
+          define_helper!();
+          define_helper!();
+        "#,
+    );
+}
+
+#[test]
+fn helper_attr_without_derive() {
+    check_diagnostics(
+        r#"
+        //- /lib.rs
+          #[serde(rename = "x")]
+        //^^^^^^^^^^^^^^^^^^^^^^ helper attribute `serde` is used without a derive that registers it
+          struct S;
+
+          enum E {
+              A {
+                  #[serde(skip)]
+                //^^^^^^^^^^^^^^ helper attribute `serde` is used without a derive that registers it
+                  field: u8,
+              },
+          }
+
+          #[derive(Serialize)]
+          #[serde(rename = "y")]
+          struct Derived;
+
+          #[derive(Deserialize)]
+          struct WithField(#[serde(default)] u8);
+        "#,
+    );
+}
+
+#[test]
+fn diagnostic_severity() {
+    let db: TestDB = TestDB::with_files(
+        r"
+        //- /lib.rs
+        use does_not_exist;
+        enum Empty {}
+        ",
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+
+    let (errors, weak_warnings): (Vec<_>, Vec<_>) =
+        def_map.diagnostics.iter().partition(|diag| diag.is_error());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(weak_warnings.len(), 1);
+    assert!(!errors[0].is_weak_warning());
+    assert!(weak_warnings[0].is_weak_warning());
+    assert!(!weak_warnings[0].is_warning());
+}
+
+#[test]
+fn diagnostic_category() {
+    let db: TestDB = TestDB::with_files(
+        r#"
+        //- /lib.rs
+        mod missing;
+        use does_not_exist;
+        m!();
+        #[cfg(never)]
+        struct Inactive;
+        trait T {
+            pub fn f();
+        }
+        "#,
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+
+    let mut categories: Vec<_> = def_map.diagnostics.iter().map(|diag| diag.category()).collect();
+    categories.sort();
+    assert_eq!(
+        categories,
+        vec![
+            DiagnosticCategory::Import,
+            DiagnosticCategory::Macro,
+            DiagnosticCategory::Cfg,
+            DiagnosticCategory::Module,
+            DiagnosticCategory::Visibility,
+        ]
+    );
+}
+
+#[test]
+fn reexport_of_private_module_item() {
+    check_diagnostics(
+        r"
+        //- /lib.rs
+        mod private {
+            pub struct Item;
+            pub(crate) struct CrateItem;
+            struct PrivateItem;
+        }
+        pub mod public {
+            pub(super) fn f() {}
+        }
+
+        pub use private::Item;
+        pub use private::CrateItem;
+              //^^^^^^^^^^^^^^^^^^ `pub use` re-exports an item that isn't public in module `private`
+        pub use private::PrivateItem;
+              //^^^^^^^^^^^^^^^^^^^^ `pub use` re-exports an item that isn't public in module `private`
+        pub use public::f;
+              //^^^^^^^^^ `pub use` re-exports an item that isn't public in module `public`
+        pub(crate) use private::CrateItem as Alias;
+        ",
+    );
+}
+
+#[test]
+fn redundant_visibility_on_trait_item() {
+    check_diagnostics(
+        r"
+        //- /lib.rs
+        pub trait T {
+              pub fn f();
+            //^^^ visibility qualifiers are not permitted on trait items
+              pub(crate) const C: u8;
+            //^^^^^^^^^^ visibility qualifiers are not permitted on trait items
+            type Ty;
+        }
+        pub struct S;
+        impl S {
+            pub fn g() {}
+        }
+        ",
+    );
+}
+
+#[test]
+fn disallowed_nested_impl_trait() {
+    check_diagnostics(
+        r"
+        //- /lib.rs
+        trait Debug {}
+        trait Into<T> {}
+        trait Iterator { type Item; }
+        fn ret() -> impl Into<impl Debug> { loop {} }
+                            //^^^^^^^^^^ nested `impl Trait` is not allowed
+        fn arg(_: impl Into<Option<impl Debug>>) {}
+                                 //^^^^^^^^^^ nested `impl Trait` is not allowed
+        fn binding() -> impl Iterator<Item = impl Debug> { loop {} }
+        fn not_nested(_: impl Into<u8>, _: impl Debug) {}
+        ",
+    );
+}
+
+#[test]
+fn duplicate_lang_item() {
+    check_diagnostics(
+        r#"
+        //- /lib.rs
+        #[lang = "sized"]
+        trait Sized {}
+        #[lang = "copy"]
+        trait Copy {}
+
+        mod m {
+              #[lang = "sized"]
+            //^^^^^^^^^^^^^^^^^ duplicate lang item `sized`
+            trait OtherSized {}
+        }
+        "#,
+    );
+}
+
+#[test]
+fn ineffective_macro_export() {
+    check_diagnostics(
+        r#"
+        //- /lib.rs
+        #[macro_export]
+        macro_rules! exported { () => {} }
+
+        fn f() {
+            // Other problems with items in blocks aren't reported yet.
+            use does_not_exist;
+
+              #[macro_export]
+            //^^^^^^^^^^^^^^^ `#[macro_export]` has no effect on macros defined inside a block
+            macro_rules! local { () => {} }
+        }
+        "#,
+    );
+}
+
+#[test]
+fn non_portable_path() {
+    // Backslashes are fine when compiling for Windows.
+    check_diagnostics(
+        r#"
+        //- /main.rs crate:main deps:win
+          #[path = "a\\b.rs"]
+        //^^^^^^^^^^^^^^^^^^^ `#[path]` uses backslashes, which only work as path separators on Windows
+        mod b {}
+        #[path = "a/c.rs"]
+        mod c {}
+
+        //- /win.rs crate:win cfg:windows
+        #[path = "a\\b.rs"]
+        mod b {}
+        "#,
+    );
+}
+
+#[test]
+fn glob_of_self() {
+    check_diagnostics(
+        r"
+        //- /lib.rs
+        mod m {
+            pub struct S;
+            use self::*;
+              //^^^^^^^ glob import of the module it is in
+            use super::m::*;
+              //^^^^^^^^^^^ glob import of the module it is in
+        }
+        use m::*;
+        ",
+    );
+}
+
+#[test]
+fn crate_self_alias() {
+    check_diagnostics(
+        r"
+        //- /main.rs crate:main deps:dep
+        pub use crate as dep;
+              //^^^^^^^^^^^^ `crate` is imported as `dep`, which shadows the extern crate of that name
+        pub use crate as main;
+
+        //- /dep.rs crate:dep
+        ",
+    );
+}
+
+#[test]
+fn invalid_repr() {
+    check_diagnostics(
+        r"
+        //- /lib.rs
+        #[repr(C)]
+        struct C;
+        #[repr(u8, align(4))]
+        enum E { A }
+          #[repr(nonsense)]
+        //^^^^^^^^^^^^^^^^^ unrecognized representation hint `nonsense`
+        union U { f: u32 }
+        ",
+    );
+}
+
+#[test]
+fn unresolved_derive_path() {
+    check_diagnostics(
+        r"
+        //- /lib.rs
+        mod m {}
+
+          #[derive(unknown::Serialize)]
+        //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ failed to resolve `unknown` in derive path `unknown::Serialize`
+        struct S;
+          #[derive(Clone, m::Missing)]
+        //^^^^^^^^^^^^^^^^^^^^^^^^^^^^ failed to resolve `Missing` in derive path `m::Missing`
+        struct T;
+        ",
+    );
+}
+
+#[test]
+fn contradictory_cfg() {
+    // Both atoms are enabled, so that the item isn't also reported as inactive.
+    check_diagnostics(
+        r#"
+        //- /lib.rs cfg:unix,windows
+          #[cfg(unix)] #[cfg(windows)] fn f() {}
+        //^^^^^^^^^^^^ the `cfg` attributes of this item contradict each other, so it is never enabled
+
+        #[cfg(unix)] #[cfg(not(target_os = "linux"))] fn g() {}
+        "#,
+    );
+}
+
+#[test]
+fn macro_expansion_too_large() {
+    let mut db: TestDB = TestDB::with_files(
+        r#"
+        //- /lib.rs
+        macro_rules! small { () => { struct A; } }
+        macro_rules! large { () => { struct B; struct C; struct D; struct E; } }
+
+        small!();
+          large!();
+        //^^^^^^^^^ macro expansion is too large (12 tokens) and was ignored
+        "#,
+    );
+    db.set_macro_token_limit(10);
+    db.check_diagnostics();
+}
+
+#[test]
+fn overlapping_impl() {
+    check_diagnostics(
+        r"
+        //- /lib.rs
+        trait Foo {}
+        struct S;
+        struct T<U>(U);
+
+        impl Foo for S {}
+          impl Foo for S {}
+        //^^^^^^^^^^^^^^^^^ conflicting implementations of trait `Foo` for type `S`
+
+        impl<U> Foo for T<U> {}
+          impl<U> Foo for T<U> {}
+        //^^^^^^^^^^^^^^^^^^^^^^^ conflicting implementations of trait `Foo` for type `T<U>`
+
+        impl S {}
+        impl S {}
+        impl<U: Foo> Foo for U {}
+        ",
+    );
+}
+
+#[test]
+fn deprecated_without_note() {
+    check_diagnostics(
+        r#"
+        //- /lib.rs
+          #[deprecated]
+        //^^^^^^^^^^^^^ deprecated item has no note explaining the deprecation
+        fn f() {}
+          #[deprecated(since = "1.0")]
+        //^^^^^^^^^^^^^^^^^^^^^^^^^^^^ deprecated item has no note explaining the deprecation
+        struct S;
+
+        #[deprecated = "use `g` instead"]
+        fn f2() {}
+        #[deprecated(since = "1.0", note = "use `T` instead")]
+        struct S2;
+        "#,
+    );
+}
+
+#[test]
+fn empty_enum() {
+    check_diagnostics(
+        r#"
+        //- /lib.rs
+        enum Never {}
+           //^^^^^ enum has no variants, so it can't be constructed
+        enum E { A, B }
+        "#,
+    );
+}
+
+#[test]
+fn unknown_tool_lint() {
+    check_diagnostics(
+        r#"
+        //- /lib.rs
+        #![register_tool(my_tool)]
+
+        #[unknown_tool::lint]
+        //^^^^^^^^^^^^ unknown tool `unknown_tool` in attribute path
+        fn f() {}
+
+        #[rustfmt::skip]
+        #[clippy::cognitive_complexity = "100"]
+        #[my_tool::attr]
+        fn g() {}
+
+        #[attrs::attr]
+        fn h() {}
+
+        mod attrs {}
+        "#,
+    );
+}
+
+#[test]
+fn collect_diagnostics_streams_each_diagnostic() {
+    let db: TestDB = TestDB::with_files(
+        r"
+        //- /lib.rs
+        use does_not_exist;
+        use foo::Unused;
+
+        mod a {
+            use does_not_exist_either;
+            mod b;
+        }
+
+        mod foo {
+            pub struct Unused;
+        }
+        ",
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+
+    let mut streamed = Vec::new();
+    DefMap::collect_diagnostics(&db, krate, &mut |diag| streamed.push(diag));
+    assert_eq!(streamed, db.crate_def_map(krate).diagnostics);
+
+    let mut codes = Vec::new();
+    let mut sink = DiagnosticSinkBuilder::new().build(|it| codes.push(it.code().0));
+    for diag in &streamed {
+        diag.add_to_sink(&db, &mut sink);
+    }
+    drop(sink);
+    codes.sort_unstable();
+    assert_eq!(codes, ["unresolved-import", "unresolved-import", "unresolved-module"]);
+}
+
+#[test]
+fn collect_diagnostics_with_min_severity() {
+    let db: TestDB = TestDB::with_files(
+        r"
+        //- /lib.rs
+        use does_not_exist;
+        use foo::Unused;
+        use self::*;
+
+        enum Empty {}
+
+        mod foo {
+            pub struct Unused;
+        }
+        ",
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let codes = |min_severity| {
+        let mut codes = Vec::new();
+        DefMap::collect_diagnostics_with_min_severity(&db, krate, min_severity, &mut |diag| {
+            codes.push(diag.code())
+        });
+        codes.sort_unstable();
+        codes
+    };
+
+    assert_eq!(codes(Severity::Error), ["unresolved-import"]);
+    assert_eq!(codes(Severity::Warning), ["glob-of-self", "unresolved-import"]);
+    assert_eq!(codes(Severity::WeakWarning), ["empty-enum", "glob-of-self", "unresolved-import"]);
+}
+
+#[test]
+fn duplicate_exported_symbol() {
+    check_diagnostics(
+        r#"
+        //- /lib.rs
+        #[no_mangle]
+        pub fn f() {}
+        #[export_name = "g"]
+        pub extern "C" fn g() {}
+        #[no_mangle]
+        pub static S: u32 = 0;
+
+        mod m {
+              #[no_mangle]
+            //^^^^^^^^^^^^ `#[no_mangle]` exports a symbol that is already exported by another item
+            pub unsafe fn f() {}
+              #[export_name = "f"]
+            //^^^^^^^^^^^^^^^^^^^^ `#[export_name]` exports a symbol that is already exported by another item
+            pub fn h() {}
+            #[no_mangle]
+            pub fn g2() {}
+            extern "C" {
+                #[no_mangle]
+                fn g();
+            }
+        }
+        "#,
+    );
+}
+
+#[test]
+fn wrong_number_of_generic_args() {
+    check_diagnostics(
+        r#"
+        //- /main.rs crate:main deps:std
+        use std::vec::Vec;
+
+        struct S {
+            a: Vec<u8, u8>,
+            b: Vec<u8>,
+        }
+        fn f(_: Vec<Vec<u8, u8, u8>>) {}
+                     //^^^^^^^^^^^^ expected 2 generic arguments, found 3
+        type Alias<T> = std::vec::Vec<T>;
+        const C: Alias<u8> = loop {};
+        static D: Alias<u8, u8> = loop {};
+                     //^^^^^^^^ expected 1 generic argument, found 2
+        impl S {
+            fn g(_: std::vec::Vec<u8, u8, u8>) {}
+                               //^^^^^^^^^^^^ expected 2 generic arguments, found 3
+        }
+
+        //- /std.rs crate:std
+        pub mod vec {
+            pub struct Vec<T, A = ()>(T, A);
+        }
+        "#,
+    );
+}
+
+#[test]
+fn unused_import() {
+    check_unused_imports(
+        r"
+        //- /lib.rs
+        use foo::Unused;
+          //^^^^^^^^^^^ unused import
+        use foo::Used;
+        use foo::{Unused as Renamed, UsedInMacro};
+                //^^^^^^^^^^^^^^^^^ unused import
+        use foo::Enum;
+        use Enum::Variant;
+        use foo::UsedInChild;
+        pub use foo::Unused as Reexported;
+        use foo::Trait as _;
+
+        macro_rules! m { ($($tt:tt)*) => {} }
+
+        fn f(_: Used, _: foo::Unused) {
+            m!(UsedInMacro);
+            let _ = Variant;
+        }
+
+        mod child {
+            fn g(_: super::UsedInChild) {}
+        }
+
+        mod foo {
+            pub struct Unused;
+            pub struct Used;
+            pub struct UsedInMacro;
+            pub struct UsedInChild;
+            pub enum Enum { Variant }
+            pub trait Trait {}
+        }
+        ",
+    );
+}
+
+#[test]
+fn unused_import_glob_imported_by_child() {
+    check_unused_imports(
+        r"
+        //- /lib.rs
+        mod foo;
+        mod tests;
+        use foo::UsedInTests;
+        use foo::Unused;
+          //^^^^^^^^^^^ unused import
+
+        //- /foo.rs
+        pub struct Unused;
+        pub struct UsedInTests;
+
+        //- /tests.rs
+        use super::*;
+
+        fn f(_: UsedInTests) {}
+        ",
+    );
+}
+
+#[test]
+fn unused_import_used_by_descendant() {
+    check_unused_imports(
+        r"
+        //- /lib.rs
+        mod a;
+        mod foo {
+            pub struct ByPath;
+            pub struct ByGlob;
+            pub struct Unused;
+        }
+        use foo::ByPath;
+        use foo::ByGlob;
+        use foo::Unused;
+          //^^^^^^^^^^^ unused import
+
+        //- /a.rs
+        mod b {
+            mod c;
+        }
+
+        //- /a/b/c.rs
+        use crate::*;
+
+        fn f(_: ByGlob, _: crate::ByPath) {}
+        ",
+    );
+}
+
+#[test]
+fn unused_import_used_in_macro_expansion() {
+    check_unused_imports(
+        r"
+        //- /lib.rs crate:main deps:macros
+        #[macro_use]
+        extern crate macros;
+        mod foo {
+            pub struct InItem;
+            pub struct InBody;
+            pub struct Unused;
+        }
+        use foo::InItem;
+        use foo::InBody;
+        use foo::Unused;
+          //^^^^^^^^^^^ unused import
+
+        item!();
+
+        fn f() {
+            body!();
+        }
+
+        //- /macros.rs crate:macros
+        #[macro_export]
+        macro_rules! item {
+            () => { fn g(_: InItem) {} };
+        }
+        #[macro_export]
+        macro_rules! body {
+            () => { let _: InBody; };
+        }
+        ",
+    );
+}
@@ -2,6 +2,8 @@ use std::sync::Arc;
 
 use base_db::SourceDatabaseExt;
 
+use crate::{AdtId, EnumVariantId, FieldId, ModuleDefId};
+
 use super::*;
 
 fn check_def_map_is_not_recomputed(ra_fixture_initial: &str, ra_fixture_change: &str) {
@@ -105,3 +107,102 @@ fn typing_inside_a_macro_should_not_invalidate_def_map() {
         assert!(!format!("{:?}", events).contains("crate_def_map"), "{:#?}", events)
     }
 }
+
+#[test]
+fn typing_inside_a_function_should_not_invalidate_field_attrs() {
+    let (mut db, pos) = TestDB::with_position(
+        r"
+        //- /lib.rs
+        enum E {
+            A { a: u32 },
+            B(#[allow(unused)] u8, u16),
+        }
+
+        fn foo() -> i32 {
+            1 + 1$0
+        }
+        ",
+    );
+    let krate = db.test_crate();
+    let field = {
+        let crate_def_map = db.crate_def_map(krate);
+        let e = crate_def_map[crate_def_map.root]
+            .scope
+            .declarations()
+            .find_map(|it| match it {
+                ModuleDefId::AdtId(AdtId::EnumId(it)) => Some(it),
+                _ => None,
+            })
+            .unwrap();
+        let variant = db.enum_data(e).variants.iter().nth(1).unwrap().0;
+        let parent = EnumVariantId { parent: e, local_id: variant }.into();
+        let local_id = db.fields_attrs(parent).iter().next().unwrap().0;
+        FieldId { parent, local_id }
+    };
+    {
+        let events = db.log_executed(|| {
+            assert!(db.attrs(field.into()).by_key("allow").exists());
+        });
+        assert!(format!("{:?}", events).contains("attrs(FieldId"), "{:#?}", events)
+    }
+    db.set_file_text(
+        pos.file_id,
+        Arc::new(
+            r"
+enum E {
+    A { a: u32 },
+    B(#[allow(unused)] u8, u16),
+}
+
+fn foo() -> i32 { 92 }
+"
+            .to_string(),
+        ),
+    );
+
+    {
+        let events = db.log_executed(|| {
+            assert!(db.attrs(field.into()).by_key("allow").exists());
+        });
+        assert!(!format!("{:?}", events).contains("attrs(FieldId"), "{:#?}", events)
+    }
+}
+
+#[test]
+fn diagnostics_since_previous_revision() {
+    let (mut db, pos) = TestDB::with_position(
+        r"
+        //- /lib.rs
+        $0mod missing;
+        use does_not_exist;
+        ",
+    );
+    let krate = db.test_crate();
+    let previous = db.crate_def_map(krate).diagnostics().to_vec();
+    assert_eq!(previous.len(), 2);
+
+    db.set_file_text(
+        pos.file_id,
+        Arc::new(
+            r"
+mod missing;
+use does_not_exist;
+enum Empty {}
+"
+            .to_string(),
+        ),
+    );
+    let delta = db.crate_def_map(krate).diagnostics_since(&previous);
+    let codes: Vec<_> = delta.added.iter().map(|it| it.code()).collect();
+    assert_eq!(codes, ["empty-enum"]);
+    assert!(delta.removed.is_empty());
+
+    db.set_file_text(pos.file_id, Arc::new("mod missing;".to_string()));
+    let delta = db.crate_def_map(krate).diagnostics_since(&previous);
+    assert!(delta.added.is_empty());
+    let codes: Vec<_> = delta.removed.iter().map(|it| it.code()).collect();
+    assert_eq!(codes, ["unresolved-import"]);
+
+    let current = db.crate_def_map(krate).diagnostics().to_vec();
+    assert!(db.crate_def_map(krate).diagnostics_since(&current).is_empty());
+}
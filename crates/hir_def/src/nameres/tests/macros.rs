@@ -1,5 +1,10 @@
 use super::*;
-use crate::nameres::proc_macro::{ProcMacroDef, ProcMacroKind};
+use hir_expand::{MacroCallKind, MacroDefKind};
+
+use crate::nameres::{
+    proc_macro::{ProcMacroDef, ProcMacroKind},
+    MacroExpansionStats,
+};
 
 #[test]
 fn macro_rules_are_globally_visible() {
@@ -665,6 +670,85 @@ fn expand_derive() {
     assert_eq!(map.modules[map.root].scope.impls().len(), 2);
 }
 
+#[test]
+fn all_macro_calls() {
+    let db = TestDB::with_files(
+        r#"
+        //- /main.rs crate:main deps:core
+        macro_rules! m { () => {} }
+
+        m!();
+        core::n!();
+
+        #[derive(core::Clone)]
+        struct S;
+
+        fn f() { m!(); }
+
+        //- /core.rs crate:core
+        #[rustc_builtin_macro]
+        pub macro Clone {}
+
+        #[macro_export]
+        macro_rules! n { () => {} }
+        "#,
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+
+    let calls: Vec<_> = db
+        .all_macro_calls(def_map.module_id(def_map.root()))
+        .iter()
+        .map(|(call, def)| {
+            let call = match call {
+                MacroCallKind::FnLike { ast_id } => {
+                    format!("{}!", ast_id.to_node(&db).path().unwrap())
+                }
+                MacroCallKind::Derive { derive_name, .. } => format!("#[derive({})]", derive_name),
+            };
+            let origin = if def.krate == krate { "local" } else { "dependency" };
+            let def = match def.kind {
+                MacroDefKind::Declarative(_) => "macro_rules",
+                MacroDefKind::BuiltInDerive(..) => "builtin derive",
+                _ => "other",
+            };
+            format!("{} -> {} ({})", call, def, origin)
+        })
+        .collect();
+    assert_eq!(
+        calls,
+        [
+            "m! -> macro_rules (local)",
+            "core::n! -> macro_rules (dependency)",
+            "#[derive(Clone)] -> builtin derive (dependency)",
+        ]
+    );
+}
+
+#[test]
+fn macro_expansion_stats() {
+    let db = TestDB::with_files(
+        r#"
+        //- /main.rs crate:main
+        macro_rules! two_structs { () => { struct A; struct B; } }
+        macro_rules! wrap { () => { two_structs!(); } }
+
+        two_structs!();
+        mod m {
+            wrap!();
+        }
+
+        fn f() { two_structs!(); }
+        "#,
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+
+    // `struct A; struct B;` is 6 tokens and `two_structs!();` is 4, with the parentheses being a
+    // single subtree. The call in `f` is not counted.
+    let stats = db.macro_expansion_stats(krate);
+    assert_eq!(stats, MacroExpansionStats { count: 3, total_tokens: 16 });
+}
+
 #[test]
 fn resolve_builtin_derive() {
     check(
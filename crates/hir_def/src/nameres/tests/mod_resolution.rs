@@ -838,3 +838,31 @@ struct AlsoShoulntAppear;
         "#]],
     )
 }
+
+#[test]
+fn module_children_in_declaration_order() {
+    let db = TestDB::with_files(
+        r#"
+//- /lib.rs
+mod c;
+mod a {}
+mod b;
+//- /c.rs
+//- /b.rs
+"#,
+    );
+    let krate = db.crate_graph().iter().next().unwrap();
+    let def_map = db.crate_def_map(krate);
+    let root = &def_map[def_map.root()];
+
+    let names: Vec<_> = db
+        .module_children(def_map.module_id(def_map.root()))
+        .iter()
+        .map(|child| {
+            let (name, _) =
+                root.children.iter().find(|(_, &local_id)| local_id == child.local_id).unwrap();
+            name.to_string()
+        })
+        .collect();
+    assert_eq!(names, ["c", "a", "b"]);
+}
@@ -47,6 +47,16 @@ pub(crate) fn lower_use_tree(
                     }
                 }
             }
+            // E.g. `use something::self;`. This isn't allowed outside of braces, but importing
+            // `something` is what the user meant, and the error is reported elsewhere.
+            if let (Some(qualifier), Some(segment)) = (ast_path.qualifier(), ast_path.segment()) {
+                if segment.kind() == Some(ast::PathSegmentKind::SelfKw) {
+                    if let Some(path) = convert_path(prefix, qualifier, hygiene) {
+                        cb(path, &tree, false, alias);
+                    }
+                    return;
+                }
+            }
             if let Some(path) = convert_path(prefix, ast_path, hygiene) {
                 cb(path, &tree, is_glob, alias)
             }
@@ -402,6 +402,18 @@ impl Resolver {
             _ => None,
         })
     }
+
+    /// Returns a resolver for the same position that doesn't see the local bindings and labels of
+    /// bodies, for paths that can only refer to items and generic parameters.
+    pub fn without_expr_scopes(&self) -> Resolver {
+        let scopes = self
+            .scopes
+            .iter()
+            .filter(|scope| !matches!(scope, Scope::ExprScope(_)))
+            .cloned()
+            .collect();
+        Resolver { scopes }
+    }
 }
 
 pub enum ScopeDef {
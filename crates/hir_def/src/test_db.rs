@@ -19,7 +19,7 @@ use crate::{
     db::DefDatabase,
     nameres::{DefMap, ModuleSource},
     src::HasSource,
-    LocalModuleId, Lookup, ModuleDefId, ModuleId,
+    AssocItemId, LocalModuleId, Lookup, ModuleDefId, ModuleId,
 };
 
 #[salsa::database(
@@ -29,12 +29,19 @@ use crate::{
     crate::db::InternDatabaseStorage,
     crate::db::DefDatabaseStorage
 )]
-#[derive(Default)]
 pub(crate) struct TestDB {
     storage: salsa::Storage<TestDB>,
     events: Mutex<Option<Vec<salsa::Event>>>,
 }
 
+impl Default for TestDB {
+    fn default() -> Self {
+        let mut this = Self { storage: Default::default(), events: Default::default() };
+        this.set_macro_token_limit(hir_expand::db::DEFAULT_TOKEN_LIMIT);
+        this
+    }
+}
+
 impl Upcast<dyn AstDatabase> for TestDB {
     fn upcast(&self) -> &(dyn AstDatabase + 'static) {
         &*self
@@ -262,7 +269,17 @@ impl TestDB {
             .collect()
     }
 
-    pub(crate) fn diagnostics<F: FnMut(&dyn Diagnostic)>(&self, mut cb: F) {
+    pub(crate) fn diagnostics<F: FnMut(&dyn Diagnostic)>(&self, cb: F) {
+        self.diagnostics_with_unused_imports(false, cb)
+    }
+
+    /// Like `diagnostics`, but also reports unused imports if `unused_imports` is set. They're
+    /// opt-in in the IDE, so most tests don't expect them.
+    fn diagnostics_with_unused_imports<F: FnMut(&dyn Diagnostic)>(
+        &self,
+        unused_imports: bool,
+        mut cb: F,
+    ) {
         let crate_graph = self.crate_graph();
         for krate in crate_graph.iter() {
             let crate_def_map = self.crate_def_map(krate);
@@ -270,24 +287,45 @@ impl TestDB {
             let mut sink = DiagnosticSinkBuilder::new().build(&mut cb);
             for (module_id, module) in crate_def_map.modules() {
                 crate_def_map.add_diagnostics(self, module_id, &mut sink);
+                if unused_imports {
+                    let id = crate_def_map.module_id(module_id);
+                    let used_traits = FxHashSet::default();
+                    crate::diagnostics::validate_imports(self, id, &used_traits, &mut sink);
+                }
 
-                for decl in module.scope.declarations() {
-                    if let ModuleDefId::FunctionId(it) = decl {
-                        let source_map = self.body_with_source_map(it.into()).1;
-                        source_map.add_diagnostics(self, &mut sink);
+                for impl_id in module.scope.impls() {
+                    crate::diagnostics::validate_impl(self, impl_id, &mut sink);
+                    for item in &self.impl_data(impl_id).items {
+                        if let AssocItemId::FunctionId(it) = item {
+                            crate::diagnostics::validate_module_item(self, (*it).into(), &mut sink);
+                        }
                     }
                 }
+                for decl in module.scope.declarations() {
+                    crate::diagnostics::validate_module_item(self, decl, &mut sink);
+                    let body = match decl {
+                        ModuleDefId::FunctionId(it) => it.into(),
+                        ModuleDefId::ConstId(it) => it.into(),
+                        ModuleDefId::StaticId(it) => it.into(),
+                        _ => continue,
+                    };
+                    crate::diagnostics::validate_body(self, body, &mut sink);
+                }
             }
         }
     }
 
     pub(crate) fn check_diagnostics(&self) {
+        self.check_diagnostics_with_unused_imports(false)
+    }
+
+    pub(crate) fn check_diagnostics_with_unused_imports(&self, unused_imports: bool) {
         let db: &TestDB = self;
         let annotations = db.extract_annotations();
         assert!(!annotations.is_empty());
 
         let mut actual: FxHashMap<FileId, Vec<(TextRange, String)>> = FxHashMap::default();
-        db.diagnostics(|d| {
+        db.diagnostics_with_unused_imports(unused_imports, |d| {
             let src = d.display_source();
             let root = db.parse_or_expand(src.file_id).unwrap();
 
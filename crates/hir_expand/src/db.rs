@@ -19,11 +19,9 @@ use crate::{
     MacroDefId, MacroDefKind, MacroFile, ProcMacroExpander,
 };
 
-/// Total limit on the number of tokens produced by any macro invocation.
-///
-/// If an invocation produces more tokens than this limit, it will not be stored in the database and
-/// an error will be emitted.
-const TOKEN_LIMIT: usize = 524288;
+/// Default limit on the number of tokens produced by any macro invocation, see
+/// [`AstDatabase::macro_token_limit`].
+pub const DEFAULT_TOKEN_LIMIT: usize = 524288;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum TokenExpander {
@@ -80,6 +78,13 @@ impl TokenExpander {
 // FIXME: rename to ExpandDatabase
 #[salsa::query_group(AstDatabaseStorage)]
 pub trait AstDatabase: SourceDatabase {
+    /// Total limit on the number of tokens produced by any macro invocation.
+    ///
+    /// If an invocation produces more tokens than this limit, it will not be stored in the
+    /// database and an error will be emitted.
+    #[salsa::input]
+    fn macro_token_limit(&self) -> usize;
+
     fn ast_id_map(&self, file_id: HirFileId) -> Arc<AstIdMap>;
 
     #[salsa::transparent]
@@ -257,11 +262,12 @@ fn macro_expand_with_arg(
     let ExpandResult { value: tt, err } = macro_rules.0.expand(db, lazy_id, &macro_arg.0);
     // Set a hard limit for the expanded tt
     let count = tt.count();
-    if count > TOKEN_LIMIT {
-        return ExpandResult::str_err(format!(
-            "macro invocation exceeds token limit: produced {} tokens, limit is {}",
-            count, TOKEN_LIMIT,
-        ));
+    let limit = db.macro_token_limit();
+    if count > limit {
+        return ExpandResult::only_err(ExpandError::TokenLimitExceeded {
+            token_count: count,
+            limit,
+        });
     }
 
     ExpandResult { value: Some(Arc::new(tt)), err }
@@ -330,6 +330,20 @@ impl MacroCallId {
     pub fn as_file(self) -> HirFileId {
         MacroFile { macro_call_id: self }.into()
     }
+
+    /// Returns the call this id was created for, together with the macro it calls.
+    pub fn call_and_def(self, db: &dyn db::AstDatabase) -> (MacroCallKind, MacroDefId) {
+        match self {
+            MacroCallId::LazyMacro(id) => {
+                let loc: MacroCallLoc = db.lookup_intern_macro(id);
+                (loc.kind, loc.def)
+            }
+            MacroCallId::EagerMacro(id) => {
+                let loc: EagerCallLoc = db.lookup_intern_eager_expansion(id);
+                (MacroCallKind::FnLike { ast_id: loc.call }, loc.def)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
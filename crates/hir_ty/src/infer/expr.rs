@@ -791,7 +791,7 @@ impl<'a> InferenceContext<'a> {
     ) -> Ty {
         for stmt in statements {
             match stmt {
-                Statement::Let { pat, type_ref, initializer } => {
+                Statement::Let { pat, type_ref, initializer, else_branch } => {
                     let decl_ty =
                         type_ref.as_ref().map(|tr| self.make_ty(tr)).unwrap_or(self.err_ty());
 
@@ -806,6 +806,17 @@ impl<'a> InferenceContext<'a> {
                         }
                     }
 
+                    if let Some(expr) = else_branch {
+                        // The `else` block only runs if the pattern doesn't match, so it doesn't
+                        // make the rest of the block diverge.
+                        let prev_diverges = mem::replace(&mut self.diverges, Diverges::Maybe);
+                        self.infer_expr_coerce(
+                            *expr,
+                            &Expectation::has_type(TyKind::Never.intern(&Interner)),
+                        );
+                        self.diverges = prev_diverges;
+                    }
+
                     let ty = self.resolve_ty_as_possible(ty);
                     self.infer_pat(*pat, &ty, BindingMode::default());
                 }
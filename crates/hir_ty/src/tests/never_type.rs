@@ -407,3 +407,36 @@ fn diverging_expression_3_break() {
         "]],
     );
 }
+
+#[test]
+fn let_else_does_not_diverge() {
+    check_infer_with_mismatches(
+        r#"
+enum Option<T> { Some(T), None }
+use Option::*;
+fn test(opt: Option<u32>) -> u32 {
+    let Some(x) = opt else { return 0; };
+    let Some(y) = opt else { 1 };
+    x
+}
+"#,
+        expect![[r#"
+            56..59 'opt': Option<u32>
+            81..166 '{     ...   x }': u32
+            91..98 'Some(x)': Option<u32>
+            96..97 'x': u32
+            101..104 'opt': Option<u32>
+            110..123 '{ return 0; }': !
+            112..120 'return 0': !
+            119..120 '0': u32
+            133..140 'Some(y)': Option<u32>
+            138..139 'y': u32
+            143..146 'opt': Option<u32>
+            152..157 '{ 1 }': i32
+            154..155 '1': i32
+            163..164 'x': u32
+            152..157: expected !, got _
+            154..155: expected !, got _
+        "#]],
+    );
+}
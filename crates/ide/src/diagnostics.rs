@@ -6,6 +6,7 @@
 
 mod fixes;
 mod field_shorthand;
+mod orphaned_module_file;
 mod unlinked_file;
 
 use std::cell::RefCell;
@@ -17,6 +18,7 @@ use hir::{
 };
 use ide_db::{base_db::SourceDatabase, RootDatabase};
 use itertools::Itertools;
+use orphaned_module_file::OrphanedModuleFile;
 use rustc_hash::FxHashSet;
 use syntax::{
     ast::{self, AstNode},
@@ -79,6 +81,8 @@ pub enum Severity {
 pub struct DiagnosticsConfig {
     pub disable_experimental: bool,
     pub disabled: FxHashSet<String>,
+    pub warn_non_ascii_identifiers: bool,
+    pub warn_unused_imports: bool,
 }
 
 pub(crate) fn diagnostics(
@@ -110,6 +114,9 @@ pub(crate) fn diagnostics(
         .on::<hir::diagnostics::UnresolvedModule, _>(|d| {
             res.borrow_mut().push(diagnostic_with_fix(d, &sema, resolve));
         })
+        .on::<hir::diagnostics::BadSelfImport, _>(|d| {
+            res.borrow_mut().push(diagnostic_with_fix(d, &sema, resolve));
+        })
         .on::<hir::diagnostics::MissingFields, _>(|d| {
             res.borrow_mut().push(diagnostic_with_fix(d, &sema, resolve));
         })
@@ -157,6 +164,12 @@ pub(crate) fn diagnostics(
                     .with_code(Some(d.code())),
             );
         })
+        .on::<OrphanedModuleFile, _>(|d| {
+            // Like for unlinked files, don't highlight the whole crate root.
+            let range = sema.diagnostics_display_range(d.display_source()).range;
+            let range = range.intersect(TextRange::up_to(TextSize::of("..."))).unwrap_or(range);
+            res.borrow_mut().push(Diagnostic::hint(range, d.message()).with_code(Some(d.code())));
+        })
         .on::<hir::diagnostics::UnresolvedProcMacro, _>(|d| {
             // Use more accurate position if available.
             let display_range = d
@@ -167,6 +180,249 @@ pub(crate) fn diagnostics(
             res.borrow_mut()
                 .push(Diagnostic::hint(display_range, d.message()).with_code(Some(d.code())));
         })
+        .on::<hir::diagnostics::UnresolvedDerivePath, _>(|d| {
+            // Underline the segment that failed to resolve, if possible.
+            let display_range = d
+                .precise_location
+                .unwrap_or_else(|| sema.diagnostics_display_range(d.display_source()).range);
+            res.borrow_mut()
+                .push(Diagnostic::error(display_range, d.message()).with_code(Some(d.code())));
+        })
+        .on::<hir::diagnostics::DuplicateLangItem, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::error(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::DisallowedNestedImplTrait, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::error(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::RedundantVisibilityOnTraitItem, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::error(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::OverlappingImpl, _>(|d| {
+            let display_range = d
+                .precise_location
+                .unwrap_or_else(|| sema.diagnostics_display_range(d.display_source()).range);
+            res.borrow_mut()
+                .push(Diagnostic::error(display_range, d.message()).with_code(Some(d.code())));
+        })
+        .on::<hir::diagnostics::DeprecatedWithoutNote, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::EmptyEnum, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::WrongNumberOfGenericArgs, _>(|d| {
+            let display_range = sema.diagnostics_display_range(d.display_source()).range;
+            res.borrow_mut()
+                .push(Diagnostic::error(display_range, d.message()).with_code(Some(d.code())));
+        })
+        .on::<hir::diagnostics::DuplicateExportedSymbol, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::UnknownToolLint, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::MacroExpansionTooLarge, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::HelperAttrWithoutDerive, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::ModuleInlineAndFile, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::DuplicateMacroDefinition, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::IneffectiveMacroExport, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::UnsupportedMacroPosition, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::ContradictoryCfg, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::PreludeShadow, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::RedundantExternCrateCore, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::NonPortablePath, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::GlobOfSelf, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::CrateSelfAlias, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::UnusedImport, _>(|d| {
+            if !config.warn_unused_imports {
+                return;
+            }
+
+            // Override severity and mark as unused.
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_unused(true)
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::ReExportOfPrivateModuleItem, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::GlobOverridesPrelude, _>(|d| {
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::NonAsciiIdentifier, _>(|d| {
+            // Non-ASCII identifiers are fine unless the user asked to be warned about them.
+            if !config.warn_non_ascii_identifiers {
+                return;
+            }
+
+            res.borrow_mut().push(
+                Diagnostic::hint(
+                    sema.diagnostics_display_range(d.display_source()).range,
+                    d.message(),
+                )
+                .with_code(Some(d.code())),
+            );
+        })
+        .on::<hir::diagnostics::MacroUsedBeforeDefinition, _>(|d| {
+            let display_range = sema.diagnostics_display_range(d.display_source()).range;
+            res.borrow_mut()
+                .push(Diagnostic::error(display_range, d.message()).with_code(Some(d.code())));
+        })
         .on::<hir::diagnostics::UnresolvedMacroCall, _>(|d| {
             let last_path_segment = sema.db.parse_or_expand(d.file).and_then(|root| {
                 d.node
@@ -199,7 +455,10 @@ pub(crate) fn diagnostics(
         });
 
     match sema.to_module_def(file_id) {
-        Some(m) => m.diagnostics(db, &mut sink),
+        Some(m) => {
+            m.diagnostics(db, &mut sink);
+            orphaned_module_file::check(&mut sink, db, file_id);
+        }
         None => {
             sink.push(UnlinkedFile { file_id, node: SyntaxNodePtr::new(&parse.tree().syntax()) });
         }
@@ -741,6 +1000,76 @@ fn test_fn() {
         );
     }
 
+    #[test]
+    fn test_unresolved_derive_path_range() {
+        check_expect(
+            r#"#[derive(unknown::Serialize)] struct S;"#,
+            expect![[r#"
+                [
+                    Diagnostic {
+                        message: "failed to resolve `unknown` in derive path `unknown::Serialize`",
+                        range: 9..16,
+                        severity: Error,
+                        fix: None,
+                        unused: false,
+                        code: Some(
+                            DiagnosticCode(
+                                "unresolved-derive-path",
+                            ),
+                        ),
+                    },
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unused_trait_import() {
+        let (analysis, file_id) = fixture::file(
+            r#"
+use m::{UsedByMethod, UsedByAssocItem, Unused};
+
+fn f(s: m::S) {
+    s.method();
+    m::S::CONST;
+}
+
+mod m {
+    pub struct S;
+    pub trait UsedByMethod { fn method(&self) {} }
+    pub trait UsedByAssocItem { const CONST: u32 = 0; }
+    pub trait Unused {}
+    impl UsedByMethod for S {}
+    impl UsedByAssocItem for S {}
+}
+"#,
+        );
+
+        let diagnostics =
+            analysis.diagnostics(&DiagnosticsConfig::default(), true, file_id).unwrap();
+        assert!(diagnostics.is_empty());
+
+        let config = DiagnosticsConfig { warn_unused_imports: true, ..Default::default() };
+        let diagnostics = analysis.diagnostics(&config, true, file_id).unwrap();
+        expect![[r#"
+            [
+                Diagnostic {
+                    message: "unused import",
+                    range: 39..45,
+                    severity: WeakWarning,
+                    fix: None,
+                    unused: true,
+                    code: Some(
+                        DiagnosticCode(
+                            "unused-import",
+                        ),
+                    ),
+                },
+            ]
+        "#]]
+        .assert_debug_eq(&diagnostics);
+    }
+
     #[test]
     fn range_mapping_out_of_macros() {
         // FIXME: this is very wrong, but somewhat tricky to fix.
@@ -857,6 +1186,22 @@ mod a {
         );
     }
 
+    #[test]
+    fn test_bad_self_import() {
+        check_fix(
+            r"
+mod foo { pub struct S; }
+use foo::$0self;
+fn f(_: foo::S) {}
+",
+            r"
+mod foo { pub struct S; }
+use foo;
+fn f(_: foo::S) {}
+",
+        );
+    }
+
     #[test]
     fn test_add_field_from_usage() {
         check_fix(
@@ -919,6 +1264,20 @@ struct Foo {
         assert!(!diagnostics.is_empty());
     }
 
+    #[test]
+    fn test_non_ascii_identifier_config() {
+        let (analysis, file_id) = fixture::file(r#"fn привет() {}"#);
+
+        let diagnostics =
+            analysis.diagnostics(&DiagnosticsConfig::default(), true, file_id).unwrap();
+        assert!(diagnostics.is_empty());
+
+        let config = DiagnosticsConfig { warn_non_ascii_identifiers: true, ..Default::default() };
+        let diagnostics = analysis.diagnostics(&config, true, file_id).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "identifier `привет` contains non-ASCII characters");
+    }
+
     #[test]
     fn test_rename_incorrect_case() {
         check_fix(
@@ -1156,4 +1515,39 @@ mod foo;
 "#,
         );
     }
+
+    #[test]
+    fn orphaned_module_file() {
+        let (analysis, files) = fixture::files(
+            r#"
+//- /main.rs crate:main
+mod foo;
+#[cfg(never)]
+mod disabled;
+//- /foo.rs
+//- /disabled.rs
+//- /orphan.rs
+//- /sub/mod.rs
+//- /bin/tool.rs crate:tool
+"#,
+        );
+        let diagnostics =
+            analysis.diagnostics(&DiagnosticsConfig::default(), true, files[0]).unwrap();
+        let messages = diagnostics
+            .iter()
+            .filter(|it| it.code.map_or(false, |code| code.as_str() == "orphaned-module-file"))
+            .map(|it| it.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "file `orphan.rs` is not included in the module tree",
+                "file `sub/mod.rs` is not included in the module tree",
+            ]
+        );
+
+        let foo_diagnostics =
+            analysis.diagnostics(&DiagnosticsConfig::default(), true, files[1]).unwrap();
+        assert!(foo_diagnostics.is_empty());
+    }
 }
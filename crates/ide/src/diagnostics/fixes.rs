@@ -3,8 +3,8 @@
 use hir::{
     db::AstDatabase,
     diagnostics::{
-        Diagnostic, IncorrectCase, MissingFields, MissingOkOrSomeInTailExpr, NoSuchField,
-        RemoveThisSemicolon, ReplaceFilterMapNextWithFindMap, UnresolvedModule,
+        BadSelfImport, Diagnostic, IncorrectCase, MissingFields, MissingOkOrSomeInTailExpr,
+        NoSuchField, RemoveThisSemicolon, ReplaceFilterMapNextWithFindMap, UnresolvedModule,
     },
     HasSource, HirDisplay, InFile, Semantics, VariantDef,
 };
@@ -58,6 +58,22 @@ impl DiagnosticWithFix for UnresolvedModule {
     }
 }
 
+impl DiagnosticWithFix for BadSelfImport {
+    fn fix(&self, sema: &Semantics<RootDatabase>, _resolve: bool) -> Option<Assist> {
+        let root = sema.db.parse_or_expand(self.file)?;
+        let path = self.node.to_node(&root).path()?;
+        let qualifier = path.qualifier()?;
+
+        // Turn `foo::self` into `foo`.
+        let range =
+            TextRange::new(qualifier.syntax().text_range().end(), path.syntax().text_range().end());
+        let edit = TextEdit::delete(range);
+        let source_change = SourceChange::from_text_edit(self.file.original_file(sema.db), edit);
+
+        Some(fix("remove_self", "Remove `self`", source_change, path.syntax().text_range()))
+    }
+}
+
 impl DiagnosticWithFix for NoSuchField {
     fn fix(&self, sema: &Semantics<RootDatabase>, _resolve: bool) -> Option<Assist> {
         let root = sema.db.parse_or_expand(self.file)?;
@@ -0,0 +1,119 @@
+//! Diagnostic emitted at a crate root for files in the crate's directory that no module includes.
+
+use hir::{
+    db::DefDatabase,
+    diagnostics::{Diagnostic, DiagnosticCode, DiagnosticSink},
+    InFile,
+};
+use ide_db::{
+    base_db::{FileId, FileLoader, SourceDatabase, SourceDatabaseExt},
+    RootDatabase,
+};
+use rustc_hash::FxHashSet;
+use syntax::{
+    ast::{self, ModuleItemOwner, NameOwner},
+    AstNode, SyntaxNodePtr,
+};
+
+// Diagnostic: orphaned-module-file
+//
+// This diagnostic is shown at the crate root for `.rs` files in the crate's directory that are not
+// part of its module tree, because no `mod` item refers to them. Such files are never compiled.
+#[derive(Debug)]
+pub(crate) struct OrphanedModuleFile {
+    pub(crate) file_id: FileId,
+    pub(crate) node: SyntaxNodePtr,
+    pub(crate) path: String,
+}
+
+impl Diagnostic for OrphanedModuleFile {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode("orphaned-module-file")
+    }
+
+    fn message(&self) -> String {
+        format!("file `{}` is not included in the module tree", self.path)
+    }
+
+    fn display_source(&self) -> InFile<SyntaxNodePtr> {
+        InFile::new(self.file_id.into(), self.node.clone())
+    }
+
+    fn as_any(&self) -> &(dyn std::any::Any + Send + 'static) {
+        self
+    }
+}
+
+/// Reports the files next to or below `file_id` that aren't part of any module tree, if `file_id`
+/// is the root of a crate.
+pub(super) fn check(sink: &mut DiagnosticSink, db: &RootDatabase, file_id: FileId) {
+    let crate_graph = db.crate_graph();
+    let is_crate_root =
+        db.relevant_crates(file_id).iter().any(|&krate| crate_graph[krate].root_file_id == file_id);
+    if !is_crate_root {
+        return;
+    }
+    let source_root = db.source_root(db.file_source_root(file_id));
+    if source_root.is_library {
+        return;
+    }
+    let crate_dir = match source_root.path_for_file(&file_id).and_then(|it| it.parent()) {
+        Some(it) => it,
+        None => return,
+    };
+
+    // Files belonging to other crates in the same source root, like `src/bin/*.rs`, aren't
+    // orphaned either.
+    let mut linked = FxHashSet::default();
+    let mut declared_mods = FxHashSet::default();
+    for krate in db.source_root_crates(db.file_source_root(file_id)).iter() {
+        linked.insert(crate_graph[*krate].root_file_id);
+        let def_map = db.crate_def_map(*krate);
+        for (_, module) in def_map.modules() {
+            let module_file = match module.origin.file_id() {
+                Some(it) => it,
+                None => continue,
+            };
+            linked.insert(module_file);
+            // A `mod` item that is cfg'd out doesn't produce a module, but still claims its file.
+            for item in db.parse(module_file).tree().items() {
+                if let ast::Item::Module(m) = item {
+                    if let (Some(name), None) = (m.name(), m.item_list()) {
+                        declared_mods.insert(name.text().to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut orphans = Vec::new();
+    for orphan in source_root.iter() {
+        if linked.contains(&orphan) {
+            continue;
+        }
+        let path = match source_root.path_for_file(&orphan) {
+            Some(it) if it.starts_with(&crate_dir) => it,
+            _ => continue,
+        };
+        let name = match path.name_and_extension() {
+            Some(("mod", Some("rs"))) => path
+                .parent()
+                .and_then(|it| it.name_and_extension().map(|(name, _)| name.to_string())),
+            Some((name, Some("rs"))) => Some(name.to_string()),
+            _ => continue,
+        };
+        if name.map_or(false, |name| declared_mods.contains(&name)) {
+            continue;
+        }
+        let path = path.to_string();
+        let crate_dir = crate_dir.to_string();
+        let relative = path.strip_prefix(&crate_dir).unwrap_or(&path).trim_start_matches('/');
+        orphans.push(relative.to_string());
+    }
+    orphans.sort();
+
+    let node = SyntaxNodePtr::new(db.parse(file_id).tree().syntax());
+    for path in orphans {
+        sink.push(OrphanedModuleFile { file_id, node: node.clone(), path });
+    }
+}
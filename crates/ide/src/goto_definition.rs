@@ -1,10 +1,17 @@
 use either::Either;
-use hir::{InFile, Semantics};
+use hir::{AsAssocItem, HasSource, InFile, PathResolution, Semantics};
 use ide_db::{
-    defs::{NameClass, NameRefClass},
+    defs::{Definition, NameClass, NameRefClass},
+    helpers::format_string::format_args_call,
+    traits::resolve_target_trait,
     RootDatabase,
 };
-use syntax::{ast, match_ast, AstNode, AstToken, SyntaxKind::*, SyntaxToken, TokenAtOffset, T};
+use syntax::{
+    ast::{self, make, FormatSpecifier, HasFormatSpecifier},
+    match_ast, AstNode, AstToken,
+    SyntaxKind::*,
+    SyntaxToken, TextRange, TextSize, TokenAtOffset, T,
+};
 
 use crate::{
     display::TryToNav,
@@ -32,6 +39,12 @@ pub(crate) fn goto_definition(
     let original_token = pick_best(file.token_at_offset(position.offset))?;
     let token = sema.descend_into_macros(original_token.clone());
     let parent = token.parent()?;
+    if let Some(string) = ast::String::cast(token.clone()) {
+        let start = original_token.text_range().start();
+        let range = format_args_capture(&string, position.offset - start)?;
+        let nav = resolve_format_args_capture(&sema, &string, &string.text()[range]);
+        return Some(RangeInfo::new(range + start, nav.into_iter().collect()));
+    }
     if let Some(_) = ast::Comment::cast(token) {
         let (attributes, def) = doc_attributes(&sema, &parent)?;
 
@@ -53,7 +66,8 @@ pub(crate) fn goto_definition(
             },
             ast::Name(name) => {
                 let def = NameClass::classify(&sema, &name)?.referenced_or_defined(sema.db);
-                def.try_to_nav(sema.db)
+                try_find_trait_item_definition(&sema, &def)
+                    .or_else(|| def.try_to_nav(sema.db))
             },
             ast::Lifetime(lt) => if let Some(name_class) = NameClass::classify_lifetime(&sema, &lt) {
                 let def = name_class.referenced_or_defined(sema.db);
@@ -68,6 +82,36 @@ pub(crate) fn goto_definition(
     Some(RangeInfo::new(original_token.text_range(), nav.into_iter().collect()))
 }
 
+/// Finds the trait item that `def` overrides or implements, if `def` is an item of a trait impl.
+fn try_find_trait_item_definition(
+    sema: &Semantics<RootDatabase>,
+    def: &Definition,
+) -> Option<NavigationTarget> {
+    let (name, assoc) = match def {
+        Definition::ModuleDef(hir::ModuleDef::Function(it)) => {
+            (it.name(sema.db), it.as_assoc_item(sema.db)?)
+        }
+        Definition::ModuleDef(hir::ModuleDef::TypeAlias(it)) => {
+            (it.name(sema.db), it.as_assoc_item(sema.db)?)
+        }
+        _ => return None,
+    };
+    let imp = match assoc.container(sema.db) {
+        hir::AssocItemContainer::Impl(imp) => imp,
+        _ => return None,
+    };
+    let trait_ = resolve_target_trait(sema, &imp.source(sema.db)?.value)?;
+    let item = trait_.items(sema.db).into_iter().find(|it| {
+        it.name(sema.db).as_ref() == Some(&name)
+            && matches!(
+                (it, assoc),
+                (hir::AssocItem::Function(_), hir::AssocItem::Function(_))
+                    | (hir::AssocItem::TypeAlias(_), hir::AssocItem::TypeAlias(_))
+            )
+    })?;
+    item.try_to_nav(sema.db)
+}
+
 fn pick_best(tokens: TokenAtOffset<SyntaxToken>) -> Option<SyntaxToken> {
     return tokens.max_by_key(priority);
     fn priority(n: &SyntaxToken) -> usize {
@@ -79,6 +123,52 @@ fn pick_best(tokens: TokenAtOffset<SyntaxToken>) -> Option<SyntaxToken> {
     }
 }
 
+/// Returns the range of the identifier at `offset` that `string` captures implicitly, like `x` in
+/// `format!("{x}")`. Offsets are relative to the start of `string`.
+fn format_args_capture(string: &ast::String, offset: TextSize) -> Option<TextRange> {
+    format_args_call(string)?;
+    let mut capture = None;
+    string.lex_format_specifier(|range, kind| {
+        if matches!(kind, FormatSpecifier::Identifier) && range.contains_inclusive(offset) {
+            capture = Some(range);
+        }
+    });
+    capture
+}
+
+fn resolve_format_args_capture(
+    sema: &Semantics<RootDatabase>,
+    string: &ast::String,
+    name: &str,
+) -> Option<NavigationTarget> {
+    let call = format_args_call(string)?;
+
+    // Explicitly named arguments, like `x` in `format_args!("{x}", x = 1)`, aren't captures.
+    let tokens: Vec<SyntaxToken> = call
+        .token_tree()?
+        .syntax()
+        .children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| !it.kind().is_trivia())
+        .collect();
+    if tokens.windows(2).any(|it| it[0].text() == name && it[1].kind() == T![=]) {
+        return None;
+    }
+
+    // The call itself isn't part of the body, so the name is resolved in the scope of the expression
+    // that the call expands to.
+    let expansion = sema
+        .expand(&call)?
+        .descendants()
+        .filter_map(ast::Expr::cast)
+        .find(|it| !matches!(it, ast::Expr::MacroStmts(_)))?;
+    let path = make::path_unqualified(make::path_segment(make::name_ref(name)));
+    match sema.scope(expansion.syntax()).speculative_resolve(&path)? {
+        PathResolution::Local(local) => Definition::Local(local).try_to_nav(sema.db),
+        _ => None,
+    }
+}
+
 pub(crate) fn reference_definition(
     sema: &Semantics<RootDatabase>,
     name_ref: Either<&ast::Lifetime, &ast::NameRef>,
@@ -274,6 +364,366 @@ fn bar() {
         );
     }
 
+    #[test]
+    fn goto_def_for_macro_defined_struct_name() {
+        check(
+            r#"
+//- /lib.rs
+macro_rules! define_struct {
+    ($name:ident) => (struct $name;)
+}
+
+define_struct!(Fo$0o);
+             //^^^
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_macro_defined_struct_in_block() {
+        check(
+            r#"
+//- /lib.rs
+macro_rules! define_struct {
+    ($name:ident) => (struct $name;)
+}
+
+fn f() {
+    define_struct!(Foo);
+                 //^^^
+    let _: Fo$0o;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_macro_defined_module_item() {
+        check(
+            r#"
+//- /lib.rs
+macro_rules! id {
+    ($($tt:tt)*) => ($($tt)*)
+}
+
+id! {
+    mod inner {
+        pub struct Foo;
+                 //^^^
+    }
+}
+
+fn f() {
+    let _: inner::Fo$0o;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_trait_in_dyn_type() {
+        check(
+            r#"
+struct Box<T: ?Sized>(*const T);
+trait MyTrait {}
+    //^^^^^^^
+fn f(_: Box<dyn MyTrait$0>) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_turbofish_arg() {
+        check(
+            r#"
+fn foo<T>() {}
+fn f() {
+    struct Bar;
+         //^^^
+    foo::<Bar$0>();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_method_call_turbofish_arg() {
+        check(
+            r#"
+struct Outer;
+     //^^^^^
+struct S;
+impl S {
+    fn method<T>(&self) {}
+}
+fn f() {
+    S.method::<Out$0er>();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_impl_type_param_in_method_signature() {
+        check(
+            r#"
+struct Foo<T>(T);
+impl<T> Foo<T> {
+   //^
+    fn get(self) -> T$0 { self.0 }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_supertrait() {
+        check(
+            r#"
+trait Super {}
+    //^^^^^
+trait Sub: Super$0 {}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_trait_in_impl_header() {
+        check(
+            r#"
+trait Trait {}
+    //^^^^^
+struct S;
+impl Trait$0 for S {}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_overridden_default_method() {
+        check(
+            r#"
+trait Trait {
+    fn provided(&self) {}
+     //^^^^^^^^
+}
+struct S;
+impl Trait for S {
+    fn provided$0(&self) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_implemented_required_method() {
+        check(
+            r#"
+trait Trait {
+    fn required(&self);
+     //^^^^^^^^
+}
+struct S;
+impl Trait for S {
+    fn required$0(&self) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_implemented_assoc_type() {
+        check(
+            r#"
+trait Trait {
+    type Item;
+       //^^^^
+}
+struct S;
+impl Trait for S {
+    type Item$0 = ();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_inherent_method_name() {
+        check(
+            r#"
+struct S;
+impl S {
+    fn method$0(&self) {}
+     //^^^^^^
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_field_type() {
+        check(
+            r#"
+struct MyType;
+     //^^^^^^
+struct S { field: MyType$0 }
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_tuple_field_type() {
+        check(
+            r#"
+struct MyType;
+     //^^^^^^
+struct S(MyType$0);
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_const_in_enum_discriminant() {
+        check(
+            r#"
+const C: isize = 1;
+    //^
+enum E {
+    A = C$0,
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_trait_in_opaque_type_alias() {
+        check(
+            r#"
+trait Trait {}
+    //^^^^^
+type Opaque = impl Trait$0;
+fn define() -> Opaque {}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_const_type_annotation() {
+        check(
+            r#"
+struct MyType;
+     //^^^^^^
+const C: MyType$0 = MyType;
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_impl_type_param_in_assoc_const_type() {
+        check(
+            r#"
+struct Foo<T>(T);
+impl<T> Foo<T> {
+   //^
+    const C: Option<T$0> = None;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_macro_2_in_block() {
+        check(
+            r#"
+fn f() {
+    macro m { () => {} }
+        //^
+    m$0!();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_const_in_array_len_of_param_type() {
+        check(
+            r#"
+const SIZE: usize = 4;
+    //^^^^
+fn f(_: [u8; SIZE$0]) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_const_in_array_len_of_let_type() {
+        check(
+            r#"
+const SIZE: usize = 4;
+    //^^^^
+fn f() {
+    let _: [u8; SIZE$0 + 1];
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_const_in_array_len_shadowed_by_local() {
+        check(
+            r#"
+const SIZE: usize = 4;
+    //^^^^
+fn f(mut SIZE: usize) {
+    let _: [u8; SIZE$0];
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_impl_type_param_in_assoc_const() {
+        check(
+            r#"
+struct Foo<T>(T);
+impl<T: Default> Foo<T> {
+   //^
+    const C: Option<T> = {
+        let t: Option<T$0> = None;
+        t
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_in_trait_const_default() {
+        check(
+            r#"
+const X: u8 = 3;
+    //^
+trait Tr {
+    const C: u8 = {
+        struct S;
+        X$0
+    };
+}
+"#,
+        );
+        check(
+            r#"
+trait Tr {
+    const C: () = {
+        struct S;
+             //^
+        let _: S$0;
+    };
+}
+"#,
+        );
+    }
+
     #[test]
     fn goto_def_for_macro_defined_fn_no_arg() {
         check(
@@ -340,6 +790,55 @@ use foo as bar$0;
         );
     }
 
+    #[test]
+    fn goto_def_for_reexported_struct() {
+        check(
+            r#"
+mod inner {
+    pub struct Foo;
+             //^^^
+}
+pub use inner::Foo$0;
+"#,
+        );
+        check(
+            r#"
+//- /lib.rs crate:main deps:foo
+fn f(_: foo::Foo$0) {}
+
+//- /foo/lib.rs crate:foo
+mod inner {
+    pub struct Foo;
+             //^^^
+}
+pub use inner::Foo;
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_use_alias_of_struct() {
+        check(
+            r#"
+mod inner {
+    pub struct Bar;
+             //^^^
+}
+use inner::Bar as Baz$0;
+"#,
+        );
+        check(
+            r#"
+mod inner {
+    pub struct Bar;
+             //^^^
+}
+use inner::Bar as Baz;
+fn f(_: Baz$0) {}
+"#,
+        );
+    }
+
     #[test]
     fn goto_def_for_use_alias_foo_macro() {
         check(
@@ -372,6 +871,98 @@ fn bar(foo: &Foo) {
         );
     }
 
+    #[test]
+    fn goto_def_for_chained_method_call() {
+        check(
+            r#"
+struct Foo;
+struct Bar;
+impl Foo {
+    fn bar(&self) -> Bar { Bar }
+}
+impl Bar {
+    fn bar(&self) {}
+     //^^^
+}
+
+fn f(foo: Foo) {
+    foo.bar().bar$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_method_call_in_struct_update_base() {
+        check(
+            r#"
+struct S { a: u8, b: u8 }
+impl S {
+    fn copy(&self) -> S { S { ..*self } }
+     //^^^^
+}
+
+fn f(base: S) {
+    S { b: 2, ..base.co$0py() };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_inherent_const() {
+        check(
+            r#"
+struct S;
+impl S {
+    const CONST: u8 = 0;
+        //^^^^^
+}
+
+fn f() {
+    S::CONST$0;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_inherent_const_in_struct_update_base() {
+        check(
+            r#"
+struct S { a: u8, b: u8 }
+impl S {
+    const DEFAULT: S = S { a: 0, b: 0 };
+        //^^^^^^^
+}
+
+fn f() {
+    S { a: 1, ..S::DEF$0AULT };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_def_for_fully_qualified_trait_method() {
+        check(
+            r#"
+trait T {
+    fn m();
+     //^
+}
+struct S;
+impl T for S {
+    fn m() {}
+}
+
+fn f() {
+    <S as T>::m$0();
+}
+"#,
+        );
+    }
+
     #[test]
     fn goto_def_for_fields() {
         check(
@@ -705,6 +1296,62 @@ fn test() {
         );
     }
 
+    #[test]
+    fn goto_format_args_capture() {
+        check(
+            r#"
+#[rustc_builtin_macro]
+macro_rules! format_args {
+    ($fmt:expr) => ({ /* compiler built-in */ });
+    ($fmt:expr, $($args:tt)*) => ({ /* compiler built-in */ })
+}
+fn test() {
+    let x = 0;
+      //^
+    format_args!("{x:?} {x$0}");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_format_args_capture_through_format() {
+        check(
+            r#"
+macro_rules! format {
+    ($($arg:tt)*) => (format_args!($($arg)*))
+}
+#[rustc_builtin_macro]
+macro_rules! format_args {
+    ($fmt:expr) => ({ /* compiler built-in */ });
+    ($fmt:expr, $($args:tt)*) => ({ /* compiler built-in */ })
+}
+fn test() {
+    let value = 0;
+      //^^^^^
+    format!("{va$0lue}");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_format_args_named_argument_is_not_a_capture() {
+        check_unresolved(
+            r#"
+#[rustc_builtin_macro]
+macro_rules! format_args {
+    ($fmt:expr) => ({ /* compiler built-in */ });
+    ($fmt:expr, $($args:tt)*) => ({ /* compiler built-in */ })
+}
+fn test() {
+    let x = 0;
+    format_args!("{{x}} {x$0}", x = 1);
+}
+"#,
+        );
+    }
+
     #[test]
     fn goto_through_included_file() {
         check(
@@ -805,6 +1452,20 @@ fn bar() {
         );
     }
 
+    #[test]
+    fn goto_def_in_macro_call_argument() {
+        check(
+            r#"
+macro_rules! foo { ($e:expr) => { $e } }
+fn bar() {}
+ //^^^
+fn main() {
+    foo!(bar$0);
+}
+"#,
+        );
+    }
+
     #[test]
     fn goto_def_for_field_init_shorthand() {
         check(
@@ -1097,6 +1758,17 @@ fn foo<T>() where T: for<'a> Foo<&'a$0 (u8, u16)>, {}
         );
     }
 
+    #[test]
+    fn goto_def_for_where_clause_bound() {
+        check(
+            r#"
+trait Clone {}
+    //^^^^^
+fn f<T>() where T: Clo$0ne {}
+"#,
+        );
+    }
+
     #[test]
     fn goto_label() {
         check(
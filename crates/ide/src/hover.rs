@@ -853,6 +853,27 @@ fn main() {
         );
     }
 
+    #[test]
+    fn hover_const_in_array_len() {
+        check(
+            r#"
+const SIZE: usize = 2;
+fn f(_: [u8; SIZE$0 + 2]) {}
+"#,
+            expect![[r#"
+                *SIZE*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const SIZE: usize
+                ```
+            "#]],
+        );
+    }
+
     #[test]
     fn hover_default_generic_types() {
         check(
@@ -1331,6 +1352,28 @@ fn foo(bar:u32) { let a = id!(ba$0r); }
         );
     }
 
+    #[test]
+    fn test_hover_through_macro_call_argument() {
+        check(
+            r#"
+macro_rules! foo { ($e:expr) => { $e } }
+fn bar() {}
+fn main() { foo!(ba$0r); }
+"#,
+            expect![[r#"
+                *bar*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                fn bar()
+                ```
+            "#]],
+        );
+    }
+
     #[test]
     fn test_hover_through_expr_in_macro_recursive() {
         check(
@@ -139,6 +139,10 @@ impl AnalysisHost {
         self.db.update_lru_capacity(lru_capacity);
     }
 
+    pub fn update_macro_token_limit(&mut self, macro_token_limit: Option<usize>) {
+        self.db.update_macro_token_limit(macro_token_limit);
+    }
+
     /// Returns a snapshot of the current state, which you can query for
     /// semantic information.
     pub fn analysis(&self) -> Analysis {
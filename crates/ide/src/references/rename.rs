@@ -1625,6 +1625,186 @@ fn foo(Foo { i: bar }: foo) -> i32 {
         )
     }
 
+    #[test]
+    fn test_rename_binding_in_nested_tuple_pat() {
+        check(
+            "c",
+            r#"
+fn f() {
+    let (a, (b$0, _)) = (1, (2, 3));
+    let _ = a + b;
+}
+"#,
+            r#"
+fn f() {
+    let (a, (c, _)) = (1, (2, 3));
+    let _ = a + c;
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn test_rename_binding_in_match_arm() {
+        check(
+            "value",
+            r#"
+enum E { A(i32), B { v: i32 }, C }
+fn f(e: E) -> i32 {
+    match e {
+        E::A(x) => x,
+        E::B { v: whole$0 @ 0 } => whole,
+        E::C => 0,
+    }
+}
+"#,
+            r#"
+enum E { A(i32), B { v: i32 }, C }
+fn f(e: E) -> i32 {
+    match e {
+        E::A(x) => x,
+        E::B { v: value @ 0 } => value,
+        E::C => 0,
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn test_rename_closure_param() {
+        check(
+            "z",
+            r#"
+fn f() {
+    let add = |x, (y$0, _): (u8, u8)| x + y;
+}
+"#,
+            r#"
+fn f() {
+    let add = |x, (z, _): (u8, u8)| x + z;
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn test_rename_struct_in_closure() {
+        check(
+            "Renamed",
+            r#"
+fn f() {
+    let c = || {
+        struct S$0;
+        let inner = |_: u32| S;
+    };
+}
+"#,
+            r#"
+fn f() {
+    let c = || {
+        struct Renamed;
+        let inner = |_: u32| Renamed;
+    };
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn test_rename_struct_in_let_else() {
+        check(
+            "Renamed",
+            r#"
+fn f(opt: Option<u8>) {
+    let Some(x) = opt else {
+        struct S$0;
+        let _ = S;
+        return;
+    };
+}
+"#,
+            r#"
+fn f(opt: Option<u8>) {
+    let Some(x) = opt else {
+        struct Renamed;
+        let _ = Renamed;
+        return;
+    };
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn test_rename_struct_in_array_repeat_count() {
+        check(
+            "Renamed",
+            r#"
+fn f() {
+    let b = [0u8; { struct S$0; let _ = S; 2 }];
+}
+"#,
+            r#"
+fn f() {
+    let b = [0u8; { struct Renamed; let _ = Renamed; 2 }];
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn test_rename_struct_in_match_arm() {
+        check(
+            "Renamed",
+            r#"
+fn f(x: Option<u8>) {
+    match x {
+        Some(_) => {
+            struct S$0;
+            let _ = S;
+        }
+        None => {}
+    }
+}
+"#,
+            r#"
+fn f(x: Option<u8>) {
+    match x {
+        Some(_) => {
+            struct Renamed;
+            let _ = Renamed;
+        }
+        None => {}
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn test_rename_struct_in_nested_inline_module() {
+        check(
+            "Renamed",
+            r#"
+mod a {
+    mod b {
+        pub struct S$0;
+    }
+    use self::b::S;
+}
+"#,
+            r#"
+mod a {
+    mod b {
+        pub struct Renamed;
+    }
+    use self::b::Renamed;
+}
+"#,
+        )
+    }
+
     #[test]
     fn test_struct_field_complex_ident_pat() {
         check(
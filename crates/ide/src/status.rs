@@ -58,6 +58,21 @@ pub(crate) fn status(db: &RootDatabase, file_id: Option<FileId>) -> String {
                     .map(|dep| format!("{}={:?}", dep.name, dep.crate_id))
                     .format(", ");
                 format_to!(buf, "deps: {}\n", deps);
+
+                let krate = hir::Crate::from(krate);
+                let summary = krate
+                    .diagnostic_summary(db)
+                    .into_iter()
+                    .sorted()
+                    .map(|(code, count)| format!("{}={}", code, count))
+                    .format(", ");
+                format_to!(buf, "diagnostics: {}\n", summary);
+                let modules = krate
+                    .modules_with_diagnostics(db)
+                    .into_iter()
+                    .map(|(module, count)| format!("{}={}", module_path(db, module), count))
+                    .format(", ");
+                format_to!(buf, "modules with diagnostics: {}\n", modules);
             }
             None => format_to!(buf, "does not belong to any crate"),
         }
@@ -66,6 +81,18 @@ pub(crate) fn status(db: &RootDatabase, file_id: Option<FileId>) -> String {
     buf
 }
 
+fn module_path(db: &RootDatabase, module: hir::Module) -> String {
+    let mut segments: Vec<_> = module
+        .path_to_root(db)
+        .into_iter()
+        .filter_map(|it| it.name(db))
+        .map(|name| name.to_string())
+        .collect();
+    segments.push("crate".to_string());
+    segments.reverse();
+    segments.join("::")
+}
+
 #[derive(Default)]
 struct FilesStats {
     total: usize,
@@ -141,6 +141,7 @@ impl RootDatabase {
             base_db::SourceRootCratesQuery
 
             // AstDatabase
+            hir::db::MacroTokenLimitQuery
             hir::db::AstIdMapQuery
             hir::db::MacroArgTextQuery
             hir::db::MacroDefQuery
@@ -152,6 +153,13 @@ impl RootDatabase {
             hir::db::FileItemTreeQuery
             hir::db::BlockDefMapQuery
             hir::db::CrateDefMapQueryQuery
+            hir::db::ModuleChildrenQuery
+            hir::db::AllMacroCallsQuery
+            hir::db::MacroExpansionStatsQuery
+            hir::db::ReexportsQuery
+            hir::db::CanonicalPathsQuery
+            hir::db::ItemsInFileQuery
+            hir::db::DiagnosticsAffectingFileQuery
             hir::db::FieldsAttrsQuery
             hir::db::VariantsAttrsQuery
             hir::db::FieldsAttrsSourceMapQuery
@@ -442,3 +442,34 @@ impl From<PathResolution> for Definition {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use base_db::fixture::WithFixture;
+    use hir::{ChildBySourceStats, Semantics};
+    use syntax::{ast, AstNode};
+
+    use crate::RootDatabase;
+
+    use super::NameClass;
+
+    #[test]
+    fn child_by_source_is_cached() {
+        let (db, position) = RootDatabase::with_position(
+            r#"
+struct Foo$0;
+"#,
+        );
+        let sema = Semantics::new(&db);
+        let file = sema.parse(position.file_id);
+        let name: ast::Name =
+            sema.find_node_at_offset_with_descend(file.syntax(), position.offset).unwrap();
+
+        NameClass::classify(&sema, &name).unwrap();
+        NameClass::classify(&sema, &name).unwrap();
+
+        let stats = sema.child_by_source_stats();
+        let module = sema.to_module_def(position.file_id).unwrap();
+        assert_eq!(stats[&module], ChildBySourceStats { computed: 1, cache_hits: 1 });
+    }
+}
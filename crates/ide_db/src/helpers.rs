@@ -1,4 +1,5 @@
 //! A module with ide helpers for high-level ide features.
+pub mod format_string;
 pub mod insert_use;
 pub mod import_assets;
 pub mod rust_doc;
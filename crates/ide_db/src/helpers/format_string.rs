@@ -0,0 +1,28 @@
+//! Tools to work with format string literals for the `format_args!` family of macros.
+use syntax::{ast, AstNode, AstToken};
+
+/// Returns whether `string` is the format string of a `format_args!` or `format_args_nl!` call.
+pub fn is_format_string(string: &ast::String) -> bool {
+    format_args_call(string).is_some()
+}
+
+/// Returns the `format_args!` or `format_args_nl!` call that `string` is the format string of.
+pub fn format_args_call(string: &ast::String) -> Option<ast::MacroCall> {
+    let parent = string.syntax().parent()?;
+
+    let call = parent.parent().and_then(ast::MacroCall::cast)?;
+    let name = call.path()?.segment()?.name_ref()?;
+    if !matches!(name.text().as_str(), "format_args" | "format_args_nl") {
+        return None;
+    }
+
+    let first_literal = parent
+        .children_with_tokens()
+        .filter_map(|it| it.as_token().cloned().and_then(ast::String::cast))
+        .next()?;
+    if &first_literal != string {
+        return None;
+    }
+
+    Some(call)
+}
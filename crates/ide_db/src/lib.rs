@@ -107,10 +107,16 @@ impl RootDatabase {
         db.set_crate_graph_with_durability(Default::default(), Durability::HIGH);
         db.set_local_roots_with_durability(Default::default(), Durability::HIGH);
         db.set_library_roots_with_durability(Default::default(), Durability::HIGH);
+        db.update_macro_token_limit(None);
         db.update_lru_capacity(lru_capacity);
         db
     }
 
+    pub fn update_macro_token_limit(&mut self, macro_token_limit: Option<usize>) {
+        let macro_token_limit = macro_token_limit.unwrap_or(hir::db::DEFAULT_TOKEN_LIMIT);
+        self.set_macro_token_limit_with_durability(macro_token_limit, Durability::HIGH);
+    }
+
     pub fn update_lru_capacity(&mut self, lru_capacity: Option<usize>) {
         let lru_capacity = lru_capacity.unwrap_or(base_db::DEFAULT_LRU_CAP);
         base_db::ParseQuery.in_db_mut(self).set_lru_capacity(lru_capacity);
@@ -40,6 +40,11 @@ pub enum ExpandError {
     ConversionError,
     ProcMacroError(tt::ExpansionError),
     UnresolvedProcMacro,
+    /// The expansion produced more tokens than allowed.
+    TokenLimitExceeded {
+        token_count: usize,
+        limit: usize,
+    },
     Other(String),
 }
 
@@ -49,6 +54,40 @@ impl From<tt::ExpansionError> for ExpandError {
     }
 }
 
+impl ExpandError {
+    /// A stable identifier for the kind of this error, which front-ends can use to look up a
+    /// localized message instead of using the `Display` output.
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            ExpandError::NoMatchingRule => "no-matching-rule",
+            ExpandError::UnexpectedToken => "unexpected-token",
+            ExpandError::BindingError(_) => "binding-error",
+            ExpandError::ConversionError => "conversion-error",
+            ExpandError::ProcMacroError(_) => "proc-macro-error",
+            ExpandError::UnresolvedProcMacro => "unresolved-proc-macro",
+            ExpandError::TokenLimitExceeded { .. } => "token-limit-exceeded",
+            ExpandError::Other(_) => "other",
+        }
+    }
+
+    /// The values to fill into the message identified by `message_id`, in order.
+    pub fn message_params(&self) -> Vec<String> {
+        match self {
+            ExpandError::NoMatchingRule
+            | ExpandError::UnexpectedToken
+            | ExpandError::ConversionError
+            | ExpandError::UnresolvedProcMacro => Vec::new(),
+            ExpandError::BindingError(message) | ExpandError::Other(message) => {
+                vec![message.clone()]
+            }
+            ExpandError::ProcMacroError(err) => vec![err.to_string()],
+            ExpandError::TokenLimitExceeded { token_count, limit } => {
+                vec![token_count.to_string(), limit.to_string()]
+            }
+        }
+    }
+}
+
 impl fmt::Display for ExpandError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -58,6 +97,11 @@ impl fmt::Display for ExpandError {
             ExpandError::ConversionError => f.write_str("could not convert tokens"),
             ExpandError::ProcMacroError(e) => e.fmt(f),
             ExpandError::UnresolvedProcMacro => f.write_str("unresolved proc macro"),
+            ExpandError::TokenLimitExceeded { token_count, limit } => write!(
+                f,
+                "macro invocation exceeds token limit: produced {} tokens, limit is {}",
+                token_count, limit
+            ),
             ExpandError::Other(e) => f.write_str(e),
         }
     }
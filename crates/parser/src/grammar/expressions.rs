@@ -158,6 +158,17 @@ pub(super) fn stmt(p: &mut Parser, with_semi: StmtWithSemi, prefer_expr: bool) {
             expressions::expr_with_attrs(p);
         }
 
+        // test let_else
+        // fn foo() {
+        //     let Some(x) = opt else { return; };
+        // }
+        if p.at(T![else]) {
+            let m = p.start();
+            p.bump(T![else]);
+            block_expr(p);
+            m.complete(p, LET_ELSE);
+        }
+
         match with_semi {
             StmtWithSemi::Yes => {
                 p.expect(T![;]);
@@ -112,6 +112,10 @@ config_data! {
         /// The warnings will be indicated by faded text or three dots in code
         /// and will not show up in the `Problems Panel`.
         diagnostics_warningsAsInfo: Vec<String> = "[]",
+        /// Whether to warn about items with non-ASCII names.
+        diagnostics_warnNonAsciiIdentifiers: bool = "false",
+        /// Whether to warn about private imports that are never used.
+        diagnostics_warnUnusedImports: bool = "false",
 
         /// Controls file watching implementation.
         files_watcher: String = "\"client\"",
@@ -173,6 +177,10 @@ config_data! {
         /// Number of syntax trees rust-analyzer keeps in memory. Defaults to 128.
         lruCapacity: Option<usize>                 = "null",
 
+        /// Maximum number of tokens a single macro call may expand to. Larger
+        /// expansions are discarded and reported. Defaults to 524288.
+        macroExpansion_tokenLimit: Option<usize>   = "null",
+
         /// Whether to show `can't find Cargo.toml` error message.
         notifications_cargoTomlNotFound: bool      = "true",
 
@@ -473,6 +481,8 @@ impl Config {
         DiagnosticsConfig {
             disable_experimental: !self.data.diagnostics_enableExperimental,
             disabled: self.data.diagnostics_disabled.clone(),
+            warn_non_ascii_identifiers: self.data.diagnostics_warnNonAsciiIdentifiers,
+            warn_unused_imports: self.data.diagnostics_warnUnusedImports,
         }
     }
     pub fn diagnostics_map(&self) -> DiagnosticsMapConfig {
@@ -485,6 +495,9 @@ impl Config {
     pub fn lru_capacity(&self) -> Option<usize> {
         self.data.lruCapacity
     }
+    pub fn macro_token_limit(&self) -> Option<usize> {
+        self.data.macroExpansion_tokenLimit
+    }
     pub fn proc_macro_srv(&self) -> Option<(PathBuf, Vec<OsString>)> {
         if !self.data.procMacro_enable {
             return None;
@@ -116,7 +116,8 @@ impl GlobalState {
             Handle { handle, receiver }
         };
 
-        let analysis_host = AnalysisHost::new(config.lru_capacity());
+        let mut analysis_host = AnalysisHost::new(config.lru_capacity());
+        analysis_host.update_macro_token_limit(config.macro_token_limit());
         let (flycheck_sender, flycheck_receiver) = unbounded();
         GlobalState {
             sender,
@@ -42,6 +42,9 @@ impl GlobalState {
         if self.config.lru_capacity() != old_config.lru_capacity() {
             self.analysis_host.update_lru_capacity(self.config.lru_capacity());
         }
+        if self.config.macro_token_limit() != old_config.macro_token_limit() {
+            self.analysis_host.update_macro_token_limit(self.config.macro_token_limit());
+        }
         if self.config.linked_projects() != old_config.linked_projects() {
             self.fetch_workspaces_request()
         } else if self.config.flycheck() != old_config.flycheck() {
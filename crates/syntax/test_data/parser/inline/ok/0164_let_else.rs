@@ -0,0 +1,3 @@
+fn foo() {
+    let Some(x) = opt else { return; };
+}
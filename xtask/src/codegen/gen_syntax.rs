@@ -6,11 +6,12 @@
 use std::{
     collections::{BTreeSet, HashSet},
     fmt::Write,
+    fs,
 };
 
 use proc_macro2::{Punct, Spacing};
 use quote::{format_ident, quote};
-use ungrammar::{rust_grammar, Grammar, Rule};
+use ungrammar::{Grammar, Rule};
 
 use crate::{
     ast_src::{AstEnumSrc, AstNodeSrc, AstSrc, Cardinality, Field, KindsSrc, KINDS_SRC},
@@ -19,7 +20,8 @@ use crate::{
 };
 
 pub(crate) fn generate_syntax() -> Result<()> {
-    let grammar = rust_grammar();
+    let grammar: Grammar =
+        fs::read_to_string(project_root().join("crates/syntax/rust.ungram"))?.parse()?;
     let ast = lower(&grammar);
 
     let syntax_kinds_file = project_root().join("crates/parser/src/syntax_kind/generated.rs");